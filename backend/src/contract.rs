@@ -1,20 +1,40 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
-mod state;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+};
 
 use linera_sdk::{
-    linera_base_types::WithContractAbi,
+    abis::fungible::{self, FungibleOperation, FungibleTokenAbi},
+    linera_base_types::{
+        Account, AccountOwner, Amount, ChainId, CryptoHash, StreamName, TimeDelta, Timestamp,
+        WithContractAbi,
+    },
     views::{RootView, View},
     Contract, ContractRuntime,
 };
 
-use jeteeah::Operation;
-
-use self::state::JeteeahState;
+use jeteeah::{
+    rules::{self, StepOutcome},
+    state::{
+        self, Achievement, AchievementDetails, AchievementKind, Challenge, ChallengeStatus,
+        DailyStreak, EndCause, Escrow, EscrowStatus, ExpiryConfig, ExpiryEntry, GameMap, GameMode,
+        GameStatus, GameSummary, JeteeahState, LedgerEntry, LedgerEntryKind, LedgerReason,
+        MoveCommitment, ProgressionConfig, RemoteRoom, ReviveConfig, Reward, RewardKind,
+        RewardPayout, Room, RoomConfig, RoomMember, RoomStatus, TickPhase, TokenConfig, Tournament,
+        TournamentDetails, TournamentEntry, DEFAULT_MAP_ID, DEFAULT_SKIN_ID, MAX_ROOM_CAPACITY,
+        MIN_ROOM_CAPACITY, SPECTATOR_FRAMES,
+    },
+    Event, Message, Operation, RoomAction, PROGRESSION_STREAM,
+};
 
 pub struct JeteeahContract {
     state: JeteeahState,
     runtime: ContractRuntime<Self>,
+    /// Rooms changed by the current operation or message, with the players who left
+    /// them. Members and leavers on other chains are sent the new state once it's done
+    updated_rooms: BTreeMap<u64, Vec<AccountOwner>>,
 }
 
 linera_sdk::contract!(JeteeahContract);
@@ -24,16 +44,20 @@ impl WithContractAbi for JeteeahContract {
 }
 
 impl Contract for JeteeahContract {
-    type Message = ();
+    type Message = Message;
     type Parameters = ();
     type InstantiationArgument = u64;
-    type EventValue = ();
+    type EventValue = Event;
 
     async fn load(runtime: ContractRuntime<Self>) -> Self {
         let state = JeteeahState::load(runtime.root_view_storage_context())
             .await
             .expect("Failed to load state");
-        JeteeahContract { state, runtime }
+        JeteeahContract {
+            state,
+            runtime,
+            updated_rooms: BTreeMap::new(),
+        }
     }
 
     async fn instantiate(&mut self, argument: Self::InstantiationArgument) {
@@ -45,25 +69,35 @@ impl Contract for JeteeahContract {
         self.state.game_width.set(20);
         self.state.game_height.set(20);
         self.state.food_spawn_rate.set(1);
+        self.state.run_step_limit.set(10_000);
+        self.state
+            .daily_reward_schedule
+            .set(vec![10, 15, 20, 25, 30, 40, 50]);
+
+        // The creator of the application administers it
+        self.state.admin.set(self.runtime.authenticated_signer());
     }
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
+        if operation.is_gameplay() {
+            self.assert_not_paused();
+        }
+
+        if let Some(action) = operation.room_action() {
+            if self.forward_room_action(action).await {
+                return;
+            }
+        }
+
         match operation {
             Operation::Increment { value } => {
                 self.state.value.set(self.state.value.get() + value);
             }
             Operation::StartGame => {
-                self.start_game().await;
+                self.start_game(false, DEFAULT_MAP_ID).await;
             }
             Operation::MoveSnake { direction } => {
-                // Convert jeteeah::state::Direction to state::Direction
-                let local_direction = match direction {
-                    jeteeah::state::Direction::Up => state::Direction::Up,
-                    jeteeah::state::Direction::Down => state::Direction::Down,
-                    jeteeah::state::Direction::Left => state::Direction::Left,
-                    jeteeah::state::Direction::Right => state::Direction::Right,
-                };
-                self.move_snake(local_direction).await;
+                self.move_snake(direction).await;
             }
             Operation::EatFood => {
                 self.eat_food().await;
@@ -74,8 +108,8 @@ impl Contract for JeteeahContract {
             Operation::ResetGame => {
                 self.reset_game().await;
             }
-            Operation::AddPoints { amount } => {
-                self.add_points(amount).await;
+            Operation::AddPoints { target, amount } => {
+                self.add_points(target, amount).await;
             }
             Operation::RedeemPoints { amount } => {
                 self.redeem_points(amount).await;
@@ -95,10 +129,225 @@ impl Contract for JeteeahContract {
             Operation::ResumeGame => {
                 self.resume_game().await;
             }
+            Operation::EmergencyPause { reason } => {
+                self.emergency_pause(reason);
+            }
+            Operation::LiftEmergencyPause => {
+                self.lift_emergency_pause();
+            }
+            Operation::TransferPoints { to, amount } => {
+                self.transfer_points(to, amount).await;
+            }
+            Operation::CreateReward {
+                name,
+                price,
+                stock,
+                kind,
+                payout,
+            } => {
+                self.create_reward(name, price, stock, kind, payout.unwrap_or_default());
+            }
+            Operation::UpdateReward {
+                reward_id,
+                name,
+                price,
+                stock,
+                kind,
+            } => {
+                self.update_reward(reward_id, name, price, stock, kind)
+                    .await;
+            }
+            Operation::RetireReward { reward_id } => {
+                self.retire_reward(reward_id).await;
+            }
+            Operation::RedeemReward { reward_id } => {
+                self.redeem_reward(reward_id).await;
+            }
+            Operation::EquipSkin { skin_id } => {
+                self.equip_skin(skin_id).await;
+            }
+            Operation::ConfigureToken { config } => {
+                self.configure_token(config);
+            }
+            Operation::StartRankedGame => {
+                self.start_game(true, DEFAULT_MAP_ID).await;
+            }
+            Operation::ClaimDaily => {
+                self.claim_daily().await;
+            }
+            Operation::SetDailyRewardSchedule { schedule } => {
+                self.set_daily_reward_schedule(schedule);
+            }
+            Operation::Revive => {
+                self.revive().await;
+            }
+            Operation::SetReviveConfig { config } => {
+                self.set_revive_config(config);
+            }
+            Operation::CreateMap {
+                name,
+                width,
+                height,
+                min_level,
+            } => {
+                self.create_map(name, width, height, min_level);
+            }
+            Operation::StartGameOnMap { map_id } => {
+                self.start_game(false, map_id).await;
+            }
+            Operation::DefineAchievement { details } => {
+                self.define_achievement(details);
+            }
+            Operation::RemoveAchievement { achievement_id } => {
+                self.remove_achievement(achievement_id).await;
+            }
+            Operation::SetProgressionConfig { config } => {
+                self.set_progression_config(config);
+            }
+            Operation::SubmitRun { game_id, inputs } => {
+                self.submit_run(game_id, inputs).await;
+            }
+            Operation::SetRunStepLimit { limit } => {
+                self.set_run_step_limit(limit);
+            }
+            Operation::SetMaxSessionDuration { duration } => {
+                self.set_max_session_duration(duration);
+            }
+            Operation::ExpireStaleGames { limit } => {
+                self.expire_stale_games(limit).await;
+            }
+            Operation::SetExpiryConfig { config } => {
+                self.set_expiry_config(config);
+            }
+            Operation::CreateRoom {
+                capacity,
+                commit_reveal,
+                stake,
+            } => {
+                self.create_room(capacity, commit_reveal, stake).await;
+            }
+            Operation::JoinRoom { code } => {
+                self.join_room(code, false).await;
+            }
+            Operation::JoinRemoteRoom { host_chain, code } => {
+                self.join_remote_room(host_chain, code).await;
+            }
+            Operation::LeaveRoom => {
+                self.leave_room().await;
+            }
+            Operation::ToggleReady => {
+                self.toggle_ready().await;
+            }
+            Operation::StartRoom => {
+                self.start_room().await;
+            }
+            Operation::SubmitMove { tick, direction } => {
+                self.submit_move(tick, direction).await;
+            }
+            Operation::CommitMove { tick, commitment } => {
+                self.commit_move(tick, commitment).await;
+            }
+            Operation::RevealMove {
+                tick,
+                direction,
+                salt,
+            } => {
+                self.reveal_move(tick, direction, salt).await;
+            }
+            Operation::ResolveTick { room_id } => {
+                self.resolve_tick_after_deadline(room_id).await;
+            }
+            Operation::SetRoomConfig { config } => {
+                self.set_room_config(config);
+            }
+            Operation::WithdrawRake { amount } => {
+                self.withdraw_rake(amount).await;
+            }
+            Operation::IssueChallenge {
+                opponent,
+                game_id,
+                stake,
+            } => {
+                self.issue_challenge(opponent, game_id, stake).await;
+            }
+            Operation::AcceptChallenge { challenge_id } => {
+                self.accept_challenge(challenge_id).await;
+            }
+            Operation::DeclineChallenge { challenge_id } => {
+                self.call_off_challenge(challenge_id, ChallengeStatus::Declined)
+                    .await;
+            }
+            Operation::CancelChallenge { challenge_id } => {
+                self.call_off_challenge(challenge_id, ChallengeStatus::Cancelled)
+                    .await;
+            }
+            Operation::CreateTournament { details } => {
+                self.create_tournament(details).await;
+            }
+            Operation::RegisterForTournament { tournament_id } => {
+                self.register_for_tournament(tournament_id).await;
+            }
+            Operation::StartTournamentGame { tournament_id } => {
+                self.start_tournament_game(tournament_id).await;
+            }
+            Operation::FinalizeTournament { tournament_id } => {
+                self.finalize_tournament(tournament_id).await;
+            }
         }
+
+        self.send_room_updates().await;
     }
 
-    async fn execute_message(&mut self, _message: Self::Message) {}
+    async fn execute_message(&mut self, message: Self::Message) {
+        match message {
+            Message::Credit { target, amount } => {
+                let sender = self
+                    .runtime
+                    .authenticated_signer()
+                    .expect("Transfers must be authenticated");
+
+                if self.runtime.message_is_bouncing() == Some(true) {
+                    // The recipient chain rejected the transfer: refund the sender
+                    self.credit_points(sender, amount, LedgerReason::transfer_refund(target))
+                        .await;
+                } else {
+                    // Rejecting the transfer while paused bounces it back to the sender
+                    self.assert_not_paused();
+                    self.credit_points(target, amount, LedgerReason::transfer(sender))
+                        .await;
+                }
+            }
+            Message::RoomAction { action } => {
+                let player = self
+                    .runtime
+                    .authenticated_signer()
+                    .expect("Room actions must be authenticated");
+
+                if self.runtime.message_is_bouncing() == Some(true) {
+                    // The host chain rejected the action. A rejected join leaves the
+                    // player free to join another room, while after a rejected leave
+                    // they still have their seat there and nothing changes here
+                    if matches!(action, RoomAction::Join { .. }) {
+                        self.state
+                            .remote_room_hosts
+                            .remove(&player)
+                            .expect("Failed to clear remote room host");
+                    }
+                } else {
+                    self.perform_room_action(player, action).await;
+                }
+            }
+            Message::RoomUpdate {
+                room,
+                board,
+                departed,
+            } => {
+                self.receive_room_update(room, board, departed).await;
+            }
+        }
+
+        self.send_room_updates().await;
+    }
 
     async fn store(mut self) {
         self.state.save().await.expect("Failed to save state");
@@ -106,64 +355,86 @@ impl Contract for JeteeahContract {
 }
 
 impl JeteeahContract {
-    /// Starts a new game for the caller
-    async fn start_game(&mut self) {
-        use self::state::{Direction, GameState, Position};
+    /// Starts a new game for the caller on the given map, charging the entry fee for
+    /// ranked play
+    async fn start_game(&mut self, ranked: bool, map_id: u32) {
+        let map = self.get_map(map_id).await;
+        self.start_game_on(ranked, map, None, None, None).await;
+    }
+
+    /// Starts a new game for the caller on a board, with a fresh seed unless one is
+    /// given, optionally for a challenge or tournament. Returns the new game's id
+    async fn start_game_on(
+        &mut self,
+        ranked: bool,
+        map: GameMap,
+        seed: Option<u64>,
+        challenge_id: Option<u64>,
+        tournament_id: Option<u64>,
+    ) -> u64 {
+        use jeteeah::state::GameState;
 
         let player = self
             .runtime
             .authenticated_signer()
             .expect("Player must be authenticated");
 
-        let width = *self.state.game_width.get();
-        let height = *self.state.game_height.get();
+        let level = self.level_of(&player).await;
+        assert!(level >= map.min_level, "Level too low for this map");
+        assert!(
+            !ranked || level >= self.state.progression.get().ranked_level,
+            "Level too low for ranked play"
+        );
 
-        // Initialize snake in the center of the board
-        let center_x = width / 2;
-        let center_y = height / 2;
+        // Any unfinished previous game is settled before it's replaced
+        self.finish_game(player, EndCause::Quit).await;
 
-        let initial_snake = vec![
-            Position {
-                x: center_x,
-                y: center_y,
-            },
-            Position {
-                x: center_x - 1,
-                y: center_y,
-            },
-            Position {
-                x: center_x - 2,
-                y: center_y,
-            },
-        ];
+        if ranked {
+            self.charge_entry_fee(player);
+        }
 
-        // Spawn initial food
-        let food_position = Position {
-            x: (center_x + 5) % width,
-            y: (center_y + 5) % height,
-        };
+        let id = *self.state.next_game_id.get();
+        self.state.next_game_id.set(id + 1);
+
+        let skin_id = self
+            .state
+            .equipped_skins
+            .get(&player)
+            .await
+            .expect("Failed to read equipped skin")
+            .unwrap_or(DEFAULT_SKIN_ID);
 
+        let started_at = self.runtime.system_time();
+        let seed = seed.unwrap_or_else(|| rules::seed_for(id, started_at));
         let game_state = GameState {
-            snake_body: initial_snake,
-            direction: Direction::Right,
-            food_position,
-            score: 0,
-            is_active: true,
-            is_paused: false,
-            width,
-            height,
+            ranked,
+            skin_id,
+            map_id: map.id,
+            started_at,
+            last_active_at: started_at,
+            config_version: *self.state.config_version.get(),
+            challenge_id,
+            tournament_id,
+            ..rules::new_game(id, seed, map.width, map.height)
         };
 
         self.state
             .games
             .insert(&player, game_state)
             .expect("Failed to insert game state");
+        self.state
+            .active_players
+            .insert(&player)
+            .expect("Failed to track active game");
+        self.state.expiry_queue.push_back(ExpiryEntry {
+            player,
+            game_id: id,
+        });
+        id
     }
 
     /// Moves the snake in the specified direction
     async fn move_snake(&mut self, new_direction: state::Direction) {
-        use self::state::{Direction, Position};
-
         let player = self
             .runtime
             .authenticated_signer()
@@ -177,48 +448,10 @@ impl JeteeahContract {
             .expect("Failed to read game state")
             .expect("Game not found");
 
-        if !game.is_active || game.is_paused {
-            return;
-        }
-
-        // Prevent reversing into itself
-        let opposite = match game.direction {
-            Direction::Up => Direction::Down,
-            Direction::Down => Direction::Up,
-            Direction::Left => Direction::Right,
-            Direction::Right => Direction::Left,
-        };
-
-        if new_direction == opposite {
+        if game.status != GameStatus::Active {
             return;
         }
-
-        game.direction = new_direction;
-
-        // Calculate new head position
-        let head = &game.snake_body[0];
-        let new_head = match game.direction {
-            Direction::Up => Position {
-                x: head.x,
-                y: head.y - 1,
-            },
-            Direction::Down => Position {
-                x: head.x,
-                y: head.y + 1,
-            },
-            Direction::Left => Position {
-                x: head.x - 1,
-                y: head.y,
-            },
-            Direction::Right => Position {
-                x: head.x + 1,
-                y: head.y,
-            },
-        };
-
-        // Check collision before moving
-        if self.check_collision_internal(&game, &new_head).await {
-            game.is_active = false;
+        if self.end_overlong_game(player, &mut game).await {
             self.state
                 .games
                 .insert(&player, game)
@@ -226,20 +459,56 @@ impl JeteeahContract {
             return;
         }
 
-        // Check if food is eaten
-        let ate_food = new_head.x == game.food_position.x && new_head.y == game.food_position.y;
+        let outcome = rules::step(&mut game, new_direction);
+        if outcome == StepOutcome::Ignored {
+            return;
+        }
+        self.apply_step(player, &mut game, new_direction, outcome)
+            .await;
+        if game.status == GameStatus::Active {
+            self.evaluate_achievements(player, &game).await;
+        }
 
-        // Add new head
-        game.snake_body.insert(0, new_head);
+        self.state
+            .games
+            .insert(&player, game)
+            .expect("Failed to update game state");
+    }
 
-        // Remove tail if no food eaten
-        if !ate_food {
-            game.snake_body.pop();
-        } else {
-            // Update score and spawn new food
-            game.score += 10;
-            game.food_position = self.spawn_food_internal(&game).await;
+    /// Records a move that changed the game and applies what it led to: a crash, or a
+    /// new high score and XP
+    async fn apply_step(
+        &mut self,
+        player: AccountOwner,
+        game: &mut state::GameState,
+        direction: state::Direction,
+        outcome: StepOutcome,
+    ) {
+        self.state
+            .replay_inputs
+            .load_entry_mut(&game.id)
+            .await
+            .expect("Failed to load replay inputs")
+            .push(direction);
+        game.last_move_at = Some(self.runtime.system_time());
+        game.last_active_at = self.runtime.system_time();
+
+        if let StepOutcome::Crashed(cause) = outcome {
+            self.mark_over(game, cause);
+            game.crashed_at = game.ended_at;
+
+            // Challenge games can't be revived, and without revives left there's nothing
+            // to wait for either
+            if game.challenge_id.is_some()
+                || game.revive_count >= self.state.revive_config.get().max_revives
+            {
+                self.settle_game(player, game).await;
+            }
+            return;
+        }
 
+        let ate_food = outcome == StepOutcome::Ate;
+        if ate_food {
             // Update high score
             let current_high = self
                 .state
@@ -254,35 +523,76 @@ impl JeteeahContract {
                     .high_scores
                     .insert(&player, game.score)
                     .expect("Failed to update high score");
+                self.state
+                    .high_score_revives
+                    .insert(&player, game.revive_count)
+                    .expect("Failed to update high score revives");
             }
         }
 
-        self.state
-            .games
-            .insert(&player, game)
-            .expect("Failed to update game state");
+        let progression = self.state.progression.get();
+        let xp = progression.xp_per_move + if ate_food { progression.xp_per_food } else { 0 };
+        self.award_xp(player, xp).await;
     }
 
-    /// Checks for collisions with walls or snake body
-    async fn check_collision_internal(
-        &self,
-        game: &state::GameState,
-        position: &state::Position,
-    ) -> bool {
-        // Wall collision
-        if position.x < 0 || position.x >= game.width || position.y < 0 || position.y >= game.height
-        {
-            return true;
+    /// Verifies a game played off-chain by replaying its inputs, then settles it with
+    /// the score the rules produce
+    async fn submit_run(&mut self, game_id: u64, inputs: Vec<state::Direction>) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        let mut game = self
+            .state
+            .games
+            .get(&player)
+            .await
+            .expect("Failed to read game state")
+            .expect("Game not found");
+
+        assert!(
+            game.id == game_id && game.status == GameStatus::Active && game.steps == 0,
+            "The run is not for the player's fresh game"
+        );
+        assert!(
+            inputs.len() as u64 <= *self.state.run_step_limit.get(),
+            "The run exceeds the step limit"
+        );
+        if self.end_overlong_game(player, &mut game).await {
+            self.state
+                .games
+                .insert(&player, game)
+                .expect("Failed to update game state");
+            return;
         }
 
-        // Self collision
-        for segment in &game.snake_body {
-            if segment.x == position.x && segment.y == position.y {
-                return true;
-            }
+        for direction in inputs {
+            assert!(
+                game.status == GameStatus::Active,
+                "The run continues after the snake crashed"
+            );
+            let outcome = rules::step(&mut game, direction);
+            assert!(
+                outcome != StepOutcome::Ignored,
+                "The run reverses the snake into itself"
+            );
+            self.apply_step(player, &mut game, direction, outcome).await;
         }
 
-        false
+        self.state
+            .games
+            .insert(&player, game)
+            .expect("Failed to update game state");
+        // Runs are final, so a crash isn't left open for a revive. Achievements are
+        // evaluated once, when the run's game is settled
+        self.finish_game(player, EndCause::Quit).await;
+    }
+
+    /// Sets the most inputs a submitted run may have
+    fn set_run_step_limit(&mut self, limit: u64) {
+        self.assert_admin();
+        self.state.run_step_limit.set(limit);
     }
 
     /// Processes food eating (called automatically in move_snake)
@@ -300,14 +610,14 @@ impl JeteeahContract {
             .expect("Failed to read game state")
             .expect("Game not found");
 
-        if !game.is_active || game.is_paused {
+        if game.status != GameStatus::Active {
             return;
         }
 
         let head = &game.snake_body[0];
         if head.x == game.food_position.x && head.y == game.food_position.y {
-            game.score += 10;
-            game.food_position = self.spawn_food_internal(&game).await;
+            game.score += rules::FOOD_SCORE;
+            game.food_position = rules::next_food(&game);
 
             self.state
                 .games
@@ -316,24 +626,23 @@ impl JeteeahContract {
         }
     }
 
-    /// Spawns food at a random position
-    async fn spawn_food_internal(&self, game: &state::GameState) -> state::Position {
-        use self::state::Position;
+    /// Ends the current game
+    async fn end_game(&mut self) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
 
-        // Simple pseudo-random food placement
-        // In production, you'd want better randomization
-        let seed = game.score + game.snake_body.len() as u64;
-        let x = (seed * 7 + 13) % game.width as u64;
-        let y = (seed * 11 + 17) % game.height as u64;
+        assert!(
+            self.get_game_state(&player).await.is_some(),
+            "Game not found"
+        );
 
-        Position {
-            x: x as i32,
-            y: y as i32,
-        }
+        self.finish_game(player, EndCause::Quit).await;
     }
 
-    /// Ends the current game
-    async fn end_game(&mut self) {
+    /// Brings a crashed snake back to life for an increasing points cost
+    async fn revive(&mut self) {
         let player = self
             .runtime
             .authenticated_signer()
@@ -347,21 +656,78 @@ impl JeteeahContract {
             .expect("Failed to read game state")
             .expect("Game not found");
 
-        game.is_active = false;
+        let config = self.state.revive_config.get().clone();
+        let crashed_at = match (game.status, game.crashed_at) {
+            (GameStatus::Over, Some(crashed_at)) => crashed_at,
+            _ => panic!("Only a crashed game can be revived"),
+        };
+        assert!(
+            game.challenge_id.is_none(),
+            "Challenge games can't be revived"
+        );
+        if self.end_overlong_game(player, &mut game).await {
+            // Past the session limit the crash stands and the game is settled instead
+            self.state
+                .games
+                .insert(&player, game)
+                .expect("Failed to update game state");
+            return;
+        }
+        assert!(
+            game.revive_count < config.max_revives,
+            "No revives left for this game"
+        );
+        assert!(
+            self.runtime.system_time() <= crashed_at.saturating_add(config.window),
+            "The revive window has closed"
+        );
+
+        let cost = config.base_cost * u64::from(game.revive_count + 1);
+        assert!(
+            self.debit_points(player, cost, LedgerReason::revive(game.id))
+                .await,
+            "Insufficient points"
+        );
+
+        game.status = GameStatus::Active;
+        game.end_cause = None;
+        game.crashed_at = None;
+        game.ended_at = None;
+        game.last_active_at = self.runtime.system_time();
+        game.revive_count += 1;
 
-        // Award points equal to score
-        let current_points = self
+        self.state
+            .games
+            .insert(&player, game)
+            .expect("Failed to update game state");
+    }
+
+    /// Sets how many revives are allowed, for how long and at what cost
+    fn set_revive_config(&mut self, config: ReviveConfig) {
+        self.assert_admin();
+        self.state.revive_config.set(config);
+        self.bump_config_version();
+    }
+
+    /// Ends the player's game if it's still running, and settles it if it hasn't been
+    async fn finish_game(&mut self, player: AccountOwner, cause: EndCause) {
+        let Some(mut game) = self
             .state
-            .points
+            .games
             .get(&player)
             .await
-            .expect("Failed to read points")
-            .unwrap_or(0);
+            .expect("Failed to read game state")
+        else {
+            return;
+        };
 
-        self.state
-            .points
-            .insert(&player, current_points + game.score)
-            .expect("Failed to update points");
+        if game.status == GameStatus::Settled {
+            return;
+        }
+        if !game.is_finished() {
+            self.mark_over(&mut game, cause);
+        }
+        self.settle_game(player, &mut game).await;
 
         self.state
             .games
@@ -369,465 +735,4691 @@ impl JeteeahContract {
             .expect("Failed to update game state");
     }
 
-    /// Resets the game for the player
-    async fn reset_game(&mut self) {
-        let player = self
-            .runtime
-            .authenticated_signer()
-            .expect("Player must be authenticated");
+    /// Marks a running game as over as of now
+    fn mark_over(&mut self, game: &mut state::GameState, cause: EndCause) {
+        game.status = GameStatus::Over;
+        game.end_cause = Some(cause);
+        game.ended_at = Some(self.runtime.system_time());
+    }
+
+    /// Ends and settles the game if it has run past the maximum session length,
+    /// returning whether it did. A crashed game keeps the cause of its crash
+    async fn end_overlong_game(
+        &mut self,
+        player: AccountOwner,
+        game: &mut state::GameState,
+    ) -> bool {
+        let Some(max_session) = *self.state.max_session.get() else {
+            return false;
+        };
+        if self.runtime.system_time() <= game.started_at.saturating_add(max_session) {
+            return false;
+        }
+
+        if !game.is_finished() {
+            self.mark_over(game, EndCause::TimeLimit);
+        }
+        self.settle_game(player, game).await;
+        true
+    }
+
+    /// Ends and settles games that ran past the session limit, were abandoned, paused
+    /// too long or left crashed after their revive window, checking the next `limit`
+    /// games in the expiry queue. Games still in play go back to the end of the queue
+    async fn expire_stale_games(&mut self, limit: u32) {
+        let entries = self
+            .state
+            .expiry_queue
+            .read_front(limit as usize)
+            .await
+            .expect("Failed to read the expiry queue");
+
+        let now = self.runtime.system_time();
+        let expiry = self.state.expiry_config.get().clone();
+        let revive_window = self.state.revive_config.get().window;
+
+        for entry in entries {
+            self.state.expiry_queue.delete_front();
+            let player = entry.player;
+            // Games that were settled or replaced since they were queued are dropped
+            let Some(mut game) = self.get_game_state(&player).await else {
+                continue;
+            };
+            if game.id != entry.game_id || game.status == GameStatus::Settled {
+                continue;
+            }
+            if !self.end_overlong_game(player, &mut game).await {
+                if !game.is_stale(now, &expiry, revive_window) {
+                    self.state.expiry_queue.push_back(entry);
+                    continue;
+                }
+                if !game.is_finished() {
+                    self.mark_over(&mut game, EndCause::Expired);
+                }
+                self.settle_game(player, &mut game).await;
+            }
+            self.state
+                .games
+                .insert(&player, game)
+                .expect("Failed to update game state");
+        }
+    }
+
+    /// Sets how long games may sit idle or paused before they can be expired
+    fn set_expiry_config(&mut self, config: ExpiryConfig) {
+        self.assert_admin();
+        self.state.expiry_config.set(config);
+    }
+
+    /// Limits how long a game may run, or lifts the limit
+    fn set_max_session_duration(&mut self, duration: Option<TimeDelta>) {
+        self.assert_admin();
+        self.state.max_session.set(duration);
+        self.bump_config_version();
+    }
 
-        // Remove the game
+    /// Pays out a finished game's score. Only an `Over` game is paid, so each game is
+    /// credited exactly once
+    async fn settle_game(&mut self, player: AccountOwner, game: &mut state::GameState) {
+        if game.status != GameStatus::Over {
+            return;
+        }
+
+        // Award points equal to score
+        self.credit_points(player, game.score, LedgerReason::game_reward(game.id))
+            .await;
+        game.status = GameStatus::Settled;
         self.state
-            .games
+            .active_players
             .remove(&player)
-            .expect("Failed to remove game");
+            .expect("Failed to untrack active game");
+        self.archive_game(player, game).await;
 
-        // Start a new game
-        self.start_game().await;
+        let games_played = self.games_played(&player).await + 1;
+        self.state
+            .games_played
+            .insert(&player, games_played)
+            .expect("Failed to update games played");
+        self.evaluate_achievements(player, game).await;
+
+        let xp = self.state.progression.get().xp_per_game;
+        self.award_xp(player, xp).await;
+
+        if let Some(challenge_id) = game.challenge_id {
+            self.complete_challenge(challenge_id, game).await;
+        }
+        if let Some(tournament_id) = game.tournament_id {
+            self.record_tournament_score(tournament_id, player, game)
+                .await;
+        }
+    }
+
+    /// Schedules a tournament players can register for right away
+    async fn create_tournament(&mut self, details: TournamentDetails) {
+        self.assert_admin();
+        assert!(
+            details.starts_at < details.ends_at,
+            "A tournament must end after it starts"
+        );
+        assert!(
+            details.max_entrants > 0,
+            "A tournament must allow at least one entrant"
+        );
+        self.get_map(details.map_id).await;
+
+        let id = *self.state.next_tournament_id.get();
+        self.state.next_tournament_id.set(id + 1);
+        self.save_tournament(Tournament {
+            id,
+            name: details.name,
+            starts_at: details.starts_at,
+            ends_at: details.ends_at,
+            mode: details.mode,
+            map_id: details.map_id,
+            entry_fee: details.entry_fee,
+            max_entrants: details.max_entrants,
+            prizes: details.prizes,
+            prize_pool: 0,
+            entries: Vec::new(),
+            finalized_at: None,
+            results: Vec::new(),
+        });
     }
 
-    /// Adds points to a player's account
-    async fn add_points(&mut self, amount: u64) {
+    /// Registers the caller for a tournament, paying its entry fee. Registration stays
+    /// open until the tournament's window closes
+    async fn register_for_tournament(&mut self, tournament_id: u64) {
         let player = self
             .runtime
             .authenticated_signer()
             .expect("Player must be authenticated");
 
-        let current_points = self
-            .state
-            .points
-            .get(&player)
-            .await
-            .expect("Failed to read points")
-            .unwrap_or(0);
+        let mut tournament = self.get_tournament(tournament_id).await;
+        assert!(
+            self.runtime.system_time() < tournament.ends_at,
+            "The tournament is over"
+        );
+        assert!(
+            tournament
+                .entries
+                .iter()
+                .all(|entry| entry.player != player),
+            "Player is already registered"
+        );
+        assert!(
+            (tournament.entries.len() as u32) < tournament.max_entrants,
+            "The tournament is full"
+        );
+        assert!(
+            self.debit_points(
+                player,
+                tournament.entry_fee,
+                LedgerReason::tournament_entry(tournament_id)
+            )
+            .await,
+            "Not enough points for the entry fee"
+        );
 
-        self.state
-            .points
-            .insert(&player, current_points + amount)
-            .expect("Failed to update points");
+        tournament.prize_pool += tournament.entry_fee;
+        tournament.entries.push(TournamentEntry {
+            player,
+            best_score: 0,
+            games: 0,
+        });
+        self.save_tournament(tournament);
     }
 
-    /// Redeems points from a player's account
-    async fn redeem_points(&mut self, amount: u64) {
+    /// Starts a game that counts towards a tournament the caller registered for, on
+    /// its map and in its mode
+    async fn start_tournament_game(&mut self, tournament_id: u64) {
         let player = self
             .runtime
             .authenticated_signer()
             .expect("Player must be authenticated");
 
-        let current_points = self
-            .state
-            .points
-            .get(&player)
-            .await
-            .expect("Failed to read points")
-            .unwrap_or(0);
+        let tournament = self.get_tournament(tournament_id).await;
+        let now = self.runtime.system_time();
+        assert!(
+            tournament.starts_at <= now && now < tournament.ends_at,
+            "The tournament isn't running"
+        );
+        assert!(
+            tournament
+                .entries
+                .iter()
+                .any(|entry| entry.player == player),
+            "Player isn't registered for the tournament"
+        );
 
-        if current_points >= amount {
-            self.state
-                .points
-                .insert(&player, current_points - amount)
-                .expect("Failed to update points");
+        let map = self.get_map(tournament.map_id).await;
+        let ranked = tournament.mode == GameMode::Ranked;
+        self.start_game_on(ranked, map, None, None, Some(tournament_id))
+            .await;
+    }
+
+    /// Counts a settled tournament game towards the player's best score, unless it
+    /// ended after the tournament's window closed or the tournament has already been
+    /// finalized
+    async fn record_tournament_score(
+        &mut self,
+        tournament_id: u64,
+        player: AccountOwner,
+        game: &state::GameState,
+    ) {
+        let mut tournament = self.get_tournament(tournament_id).await;
+        let ended_at = game.ended_at.unwrap_or_else(|| self.runtime.system_time());
+        if tournament.finalized_at.is_some() || ended_at > tournament.ends_at {
+            return;
         }
+        let Some(entry) = tournament
+            .entries
+            .iter_mut()
+            .find(|entry| entry.player == player)
+        else {
+            return;
+        };
+        entry.best_score = entry.best_score.max(game.score);
+        entry.games += 1;
+        self.save_tournament(tournament);
     }
 
-    /// Sets the game board dimensions
-    async fn set_game_parameters(&mut self, width: i32, height: i32) {
-        self.state.game_width.set(width);
-        self.state.game_height.set(height);
+    /// Ranks a tournament's entrants once its window has closed and pays out the prize
+    /// table from the entry fees, keeping what's left over as rake. Games still
+    /// unsettled by then don't count. Anyone may call it
+    async fn finalize_tournament(&mut self, tournament_id: u64) {
+        let mut tournament = self.get_tournament(tournament_id).await;
+        let now = self.runtime.system_time();
+        assert!(now >= tournament.ends_at, "The tournament is still running");
+        assert!(
+            tournament.finalized_at.is_none(),
+            "The tournament was already finalized"
+        );
+
+        // Placements past the prize table win nothing, so only the paid ones are kept
+        let mut results = tournament.standings();
+        results.truncate(tournament.prizes.len());
+        for standing in results.iter().filter(|standing| standing.prize > 0) {
+            self.credit_points(
+                standing.player,
+                standing.prize,
+                LedgerReason::tournament_prize(tournament_id),
+            )
+            .await;
+        }
+        let paid = results.iter().map(|standing| standing.prize).sum::<u64>();
+        let collected = *self.state.collected_rake.get();
+        self.state
+            .collected_rake
+            .set(collected + tournament.prize_pool - paid);
+        tournament.results = results;
+        tournament.finalized_at = Some(now);
+        self.save_tournament(tournament);
     }
 
-    /// Updates the food spawn rate
-    async fn update_food_spawn_rate(&mut self, rate: u64) {
-        self.state.food_spawn_rate.set(rate);
+    async fn get_tournament(&self, tournament_id: u64) -> Tournament {
+        self.state
+            .tournaments
+            .get(&tournament_id)
+            .await
+            .expect("Failed to read tournament")
+            .expect("Tournament not found")
     }
 
-    /// Resets the leaderboard (clears all high scores)
-    async fn reset_leaderboard(&mut self) {
-        // Note: In a production system, you'd iterate and clear all entries
-        // For now, this is a placeholder that would need proper iteration
-        // over the MapView to clear all entries
+    fn save_tournament(&mut self, tournament: Tournament) {
+        let tournament_id = tournament.id;
+        self.state
+            .tournaments
+            .insert(&tournament_id, tournament)
+            .expect("Failed to update tournament");
     }
 
-    /// Pauses the current game
-    async fn pause_game(&mut self) {
+    /// Challenges another player to beat one of the caller's settled runs on its seed,
+    /// putting up the stake
+    async fn issue_challenge(&mut self, opponent: AccountOwner, game_id: u64, stake: u64) {
         let player = self
             .runtime
             .authenticated_signer()
             .expect("Player must be authenticated");
 
-        let mut game = self
+        assert_ne!(opponent, player, "Players can't challenge themselves");
+        let run = self
             .state
-            .games
-            .get(&player)
+            .archived_games
+            .get(&game_id)
             .await
-            .expect("Failed to read game state")
+            .expect("Failed to read archived game")
             .expect("Game not found");
+        assert_eq!(
+            run.player, player,
+            "Challenges are issued on the player's own runs"
+        );
+        assert_eq!(
+            run.revive_count, 0,
+            "Runs that used revives can't be raced, as the opponent can't revive"
+        );
+
+        let id = *self.state.next_challenge_id.get();
+        self.state.next_challenge_id.set(id + 1);
+        assert!(
+            self.debit_points(player, stake, LedgerReason::challenge_stake(id))
+                .await,
+            "Not enough points for the stake"
+        );
 
-        if game.is_active {
-            game.is_paused = true;
+        let challenge = Challenge {
+            id,
+            challenger: player,
+            opponent,
+            game_id,
+            map_id: run.map_id,
+            width: run.width,
+            height: run.height,
+            seed: run.seed,
+            stake,
+            challenger_score: run.score,
+            opponent_game_id: None,
+            opponent_score: None,
+            winner: None,
+            status: ChallengeStatus::Pending,
+            created_at: self.runtime.system_time(),
+            completed_at: None,
+        };
+        for side in [player, opponent] {
             self.state
-                .games
-                .insert(&player, game)
-                .expect("Failed to update game state");
+                .player_challenges
+                .load_entry_mut(&side)
+                .await
+                .expect("Failed to load player challenges")
+                .push(id);
         }
+        self.save_challenge(challenge);
     }
 
-    /// Resumes a paused game
-    async fn resume_game(&mut self) {
+    /// Takes up a challenge: the caller matches the stake and starts a game on the
+    /// challenge's seed and board, which settles the challenge once it's over
+    async fn accept_challenge(&mut self, challenge_id: u64) {
         let player = self
             .runtime
             .authenticated_signer()
             .expect("Player must be authenticated");
 
-        let mut game = self
-            .state
-            .games
-            .get(&player)
-            .await
-            .expect("Failed to read game state")
-            .expect("Game not found");
+        let mut challenge = self.get_challenge(challenge_id).await;
+        assert_eq!(
+            challenge.opponent, player,
+            "The challenge is for another player"
+        );
+        assert_eq!(
+            challenge.status,
+            ChallengeStatus::Pending,
+            "The challenge is no longer open"
+        );
+        assert!(
+            self.debit_points(
+                player,
+                challenge.stake,
+                LedgerReason::challenge_stake(challenge_id)
+            )
+            .await,
+            "Not enough points for the stake"
+        );
 
-        if game.is_active && game.is_paused {
-            game.is_paused = false;
-            self.state
-                .games
-                .insert(&player, game)
-                .expect("Failed to update game state");
+        let map = GameMap {
+            width: challenge.width,
+            height: challenge.height,
+            ..self.get_map(challenge.map_id).await
+        };
+        let game_id = self
+            .start_game_on(false, map, Some(challenge.seed), Some(challenge_id), None)
+            .await;
+        challenge.status = ChallengeStatus::Accepted;
+        challenge.opponent_game_id = Some(game_id);
+        self.save_challenge(challenge);
+    }
+
+    /// Declines a challenge as its opponent, or withdraws it as its challenger, before
+    /// it's taken up. The challenger's stake is returned
+    async fn call_off_challenge(&mut self, challenge_id: u64, status: ChallengeStatus) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        let mut challenge = self.get_challenge(challenge_id).await;
+        let allowed = match status {
+            ChallengeStatus::Declined => challenge.opponent,
+            _ => challenge.challenger,
+        };
+        assert_eq!(
+            allowed, player,
+            "Only the opponent can decline a challenge, and only the challenger can cancel it"
+        );
+        assert_eq!(
+            challenge.status,
+            ChallengeStatus::Pending,
+            "The challenge is no longer open"
+        );
+
+        self.credit_points(
+            challenge.challenger,
+            challenge.stake,
+            LedgerReason::challenge_refund(challenge_id),
+        )
+        .await;
+        challenge.status = status;
+        challenge.completed_at = Some(self.runtime.system_time());
+        self.save_challenge(challenge);
+    }
+
+    /// Compares the opponent's settled game with the challenger's run and settles the
+    /// stakes: the better score takes both, and a draw returns them
+    async fn complete_challenge(&mut self, challenge_id: u64, game: &state::GameState) {
+        let mut challenge = self.get_challenge(challenge_id).await;
+        if challenge.status != ChallengeStatus::Accepted
+            || challenge.opponent_game_id != Some(game.id)
+        {
+            return;
+        }
+
+        challenge.opponent_score = Some(game.score);
+        challenge.winner = match game.score.cmp(&challenge.challenger_score) {
+            Ordering::Greater => Some(challenge.opponent),
+            Ordering::Less => Some(challenge.challenger),
+            Ordering::Equal => None,
+        };
+        match challenge.winner {
+            Some(winner) => {
+                self.credit_points(
+                    winner,
+                    challenge.stake * 2,
+                    LedgerReason::challenge_winnings(challenge_id),
+                )
+                .await;
+            }
+            None => {
+                for side in [challenge.challenger, challenge.opponent] {
+                    self.credit_points(
+                        side,
+                        challenge.stake,
+                        LedgerReason::challenge_refund(challenge_id),
+                    )
+                    .await;
+                }
+            }
         }
+        challenge.status = ChallengeStatus::Completed;
+        challenge.completed_at = Some(self.runtime.system_time());
+        self.save_challenge(challenge);
     }
 
-    /// Gets the game state for a player (helper for queries)
-    pub async fn get_game_state(&self, player: &linera_sdk::linera_base_types::AccountOwner) -> Option<state::GameState> {
+    async fn get_challenge(&self, challenge_id: u64) -> Challenge {
         self.state
-            .games
-            .get(player)
+            .challenges
+            .get(&challenge_id)
             .await
-            .expect("Failed to read game state")
+            .expect("Failed to read challenge")
+            .expect("Challenge not found")
     }
 
-    /// Gets the high score for a player
-    pub async fn get_high_score(&self, player: &linera_sdk::linera_base_types::AccountOwner) -> u64 {
+    fn save_challenge(&mut self, challenge: Challenge) {
+        let challenge_id = challenge.id;
         self.state
-            .high_scores
-            .get(player)
-            .await
-            .expect("Failed to read high score")
-            .unwrap_or(0)
+            .challenges
+            .insert(&challenge_id, challenge)
+            .expect("Failed to update challenge");
     }
 
-    /// Gets points for a player
-    pub async fn get_points(&self, player: &linera_sdk::linera_base_types::AccountOwner) -> u64 {
+    /// Stores the summary of a settled game in the archive and the player's history
+    async fn archive_game(&mut self, player: AccountOwner, game: &state::GameState) {
+        let ended_at = game.ended_at.unwrap_or_else(|| self.runtime.system_time());
+        let duration = ended_at.delta_since(game.started_at);
+        let summary = GameSummary {
+            id: game.id,
+            player,
+            score: game.score,
+            length: game.snake_body.len() as u64,
+            steps: game.steps,
+            started_at: game.started_at,
+            ended_at,
+            duration,
+            mode: game.mode(),
+            map_id: game.map_id,
+            width: game.width,
+            height: game.height,
+            seed: game.seed,
+            end_cause: game.end_cause,
+            revive_count: game.revive_count,
+            config_version: game.config_version,
+            tournament_id: game.tournament_id,
+        };
+
         self.state
-            .points
-            .get(player)
+            .archived_games
+            .insert(&game.id, summary)
+            .expect("Failed to archive game");
+        self.state
+            .game_history
+            .load_entry_mut(&player)
             .await
-            .expect("Failed to read points")
-            .unwrap_or(0)
+            .expect("Failed to load game history")
+            .push(game.id);
+
+        let play_time = self
+            .state
+            .play_time
+            .get(&player)
+            .await
+            .expect("Failed to read play time")
+            .unwrap_or(TimeDelta::ZERO);
+        self.state
+            .play_time
+            .insert(&player, play_time.saturating_add(duration))
+            .expect("Failed to update play time");
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use futures::FutureExt as _;
-    use linera_sdk::{util::BlockingWait, views::View, Contract, ContractRuntime};
+    /// Resets the game for the player
+    async fn reset_game(&mut self) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
 
-    use jeteeah::Operation;
+        let map_id = self
+            .get_game_state(&player)
+            .await
+            .map_or(DEFAULT_MAP_ID, |game| game.map_id);
 
-    use super::{JeteeahContract, JeteeahState};
+        // Settle and remove the game
+        self.finish_game(player, EndCause::Quit).await;
+        self.state
+            .games
+            .remove(&player)
+            .expect("Failed to remove game");
 
-    #[test]
-    fn operation() {
-        let initial_value = 10u64;
-        let mut app = create_and_instantiate_app(initial_value);
+        // Start a new game on the same map
+        self.start_game(false, map_id).await;
+    }
 
-        let increment = 10u64;
+    /// Opens a room with the caller as host and first member
+    async fn create_room(&mut self, capacity: u32, commit_reveal: bool, stake: u64) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
 
-        let _response = app
-            .execute_operation(Operation::Increment { value: increment })
-            .now_or_never()
-            .expect("Execution of application operation should not await anything");
+        assert!(
+            (MIN_ROOM_CAPACITY..=MAX_ROOM_CAPACITY).contains(&capacity),
+            "Room capacity must be between {MIN_ROOM_CAPACITY} and {MAX_ROOM_CAPACITY}"
+        );
+        self.assert_not_in_room(&player).await;
+        assert!(
+            self.get_points(&player).await >= stake,
+            "Not enough points for the room's stake"
+        );
 
-        assert_eq!(*app.state.value.get(), initial_value + increment);
+        let id = *self.state.next_room_id.get();
+        self.state.next_room_id.set(id + 1);
+        let created_at = self.runtime.system_time();
+        let code = self.unused_room_code(id, created_at).await;
+
+        let room = Room {
+            id,
+            code: code.clone(),
+            host: player,
+            capacity,
+            members: vec![RoomMember {
+                player,
+                ready: false,
+            }],
+            status: RoomStatus::Waiting,
+            created_at,
+            commit_reveal,
+            stake,
+            results: Vec::new(),
+        };
+        self.state
+            .room_codes
+            .insert(&code, id)
+            .expect("Failed to register room code");
+        self.state
+            .player_rooms
+            .insert(&player, id)
+            .expect("Failed to record player room");
+        self.save_room(room);
     }
 
-    #[test]
-    fn test_start_game() {
-        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
-
-        let initial_value = 0u64;
-        let mut app = create_and_instantiate_app(initial_value);
-
-        // Set up authentication for testing
-        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
-        app.runtime.set_authenticated_signer(Some(test_owner));
+    /// Picks a six-character code no open room is using
+    async fn unused_room_code(&self, room_id: u64, created_at: Timestamp) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
 
-        // Start a new game
-        let _response = app
-            .execute_operation(Operation::StartGame)
-            .now_or_never()
-            .expect("Start game operation should not await anything");
+        let mut value = rules::mix(room_id ^ rules::mix(created_at.micros()));
+        loop {
+            let code = (0..6)
+                .map(|index| ALPHABET[(value >> (index * 5)) as usize % ALPHABET.len()] as char)
+                .collect::<String>();
+            if !self
+                .state
+                .room_codes
+                .contains_key(&code)
+                .await
+                .expect("Failed to read room codes")
+            {
+                return code;
+            }
+            value = rules::mix(value);
+        }
+    }
 
-        // Get the test owner
-        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+    /// Adds the caller to the lobby with the given code, on behalf of their own chain if
+    /// `remote`
+    async fn join_room(&mut self, code: String, remote: bool) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
 
-        let game_state = app
+        self.assert_not_in_room(&player).await;
+        let room_id = self
             .state
-            .games
-            .get(&player)
-            .blocking_wait()
-            .expect("Failed to read game state")
-            .expect("Game state should exist");
+            .room_codes
+            .get(&code)
+            .await
+            .expect("Failed to read room codes")
+            .expect("Room not found");
+        let mut room = self.get_room(room_id).await;
 
-        // Verify initial snake position and length
         assert_eq!(
-            game_state.snake_body.len(),
-            3,
-            "Snake should start with 3 segments"
+            room.status,
+            RoomStatus::Waiting,
+            "The room has already started"
+        );
+        assert!(!room.is_full(), "The room is full");
+        // Stakes are taken from points on this chain, which remote players don't hold
+        assert!(
+            !remote || room.stake == 0,
+            "Wagered rooms can only be joined on their host chain"
+        );
+        assert!(
+            self.get_points(&player).await >= room.stake,
+            "Not enough points for the room's stake"
         );
-        assert!(game_state.is_active, "Game should be active");
-        assert!(!game_state.is_paused, "Game should not be paused");
-        assert_eq!(game_state.score, 0, "Initial score should be 0");
 
-        // Verify game board dimensions
-        assert_eq!(game_state.width, 20);
-        assert_eq!(game_state.height, 20);
+        room.members.push(RoomMember {
+            player,
+            ready: false,
+        });
+        self.state
+            .player_rooms
+            .insert(&player, room_id)
+            .expect("Failed to record player room");
+        self.save_room(room);
+    }
 
-        // Verify snake is in center area
-        let head = &game_state.snake_body[0];
-        assert_eq!(head.x, 10); // center x
-        assert_eq!(head.y, 10); // center y
+    /// Takes the caller out of their room's lobby. The next member takes over as host,
+    /// and a room left empty is cancelled. Leaving a started room before its first tick
+    /// cancels it for everyone
+    async fn leave_room(&mut self) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        let mut room = self.current_room(&player).await;
+        if room.status == RoomStatus::Playing {
+            let board = self.get_board(room.id).await;
+            assert_eq!(
+                board.tick, 0,
+                "Players can't leave a room once its game is under way"
+            );
+            self.cancel_room(room).await;
+            return;
+        }
+
+        room.members.retain(|member| member.player != player);
+        self.state
+            .player_rooms
+            .remove(&player)
+            .expect("Failed to clear player room");
+        // The player's own chain learns they're out from the room update
+        self.updated_rooms.entry(room.id).or_default().push(player);
+
+        match room.members.first() {
+            Some(next) => {
+                if room.host == player {
+                    room.host = next.player;
+                }
+            }
+            None => {
+                room.status = RoomStatus::Cancelled;
+                self.state
+                    .room_codes
+                    .remove(&room.code)
+                    .expect("Failed to release room code");
+            }
+        }
+        self.save_room(room);
     }
 
-    #[test]
-    fn test_move_snake() {
-        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
-        use jeteeah::state::Direction;
+    /// Flips the caller's ready flag in their room's lobby
+    async fn toggle_ready(&mut self) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
 
-        let initial_value = 0u64;
-        let mut app = create_and_instantiate_app(initial_value);
+        let mut room = self.current_room(&player).await;
+        assert_eq!(
+            room.status,
+            RoomStatus::Waiting,
+            "The room has already started"
+        );
 
-        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
-        app.runtime.set_authenticated_signer(Some(test_owner));
+        let member = room
+            .members
+            .iter_mut()
+            .find(|member| member.player == player)
+            .expect("Player is not in the room");
+        member.ready = !member.ready;
+        if member.ready {
+            assert!(
+                self.get_points(&player).await >= room.stake,
+                "Not enough points for the room's stake"
+            );
+        }
+        self.save_room(room);
+    }
 
-        // Start game
-        app.execute_operation(Operation::StartGame)
-            .now_or_never()
-            .expect("Start game should not await");
+    /// Starts the caller's room once every member is ready. Only the host may start it
+    async fn start_room(&mut self) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
 
-        // Move right
-        app.execute_operation(Operation::MoveSnake { direction: Direction::Right })
-            .now_or_never()
-            .expect("Move snake should not await");
+        let mut room = self.current_room(&player).await;
+        assert_eq!(room.host, player, "Only the host can start the room");
+        assert_eq!(
+            room.status,
+            RoomStatus::Waiting,
+            "The room has already started"
+        );
+        assert!(
+            room.can_start(),
+            "A room needs at least {MIN_ROOM_CAPACITY} players, all of them ready"
+        );
 
-        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
-        let game_state = app.state.games.get(&player).blocking_wait()
-            .expect("Failed to read").expect("Game should exist");
+        // Members who spent their points after readying up can't cover the stake any
+        // more, so they're sent back to the lobby instead of holding the room up
+        let mut short = false;
+        for member in &mut room.members {
+            if self.get_points(&member.player).await < room.stake {
+                member.ready = false;
+                short = true;
+            }
+        }
+        if short {
+            self.save_room(room);
+            return;
+        }
+
+        room.status = RoomStatus::Playing;
+        self.state
+            .room_codes
+            .remove(&room.code)
+            .expect("Failed to release room code");
+
+        let players = room
+            .members
+            .iter()
+            .map(|member| member.player)
+            .collect::<Vec<_>>();
+        if room.stake > 0 {
+            self.lock_stakes(room.id, room.stake, &players).await;
+        }
+
+        let seed = rules::mix(room.id ^ rules::mix(self.runtime.system_time().micros()));
+        let mut board = rules::new_board(
+            room.id,
+            seed,
+            *self.state.game_width.get(),
+            *self.state.game_height.get(),
+            &players,
+        );
+        board.commit_reveal = room.commit_reveal;
+        board.tick_deadline = self.next_tick_deadline();
+        self.state
+            .playing_rooms
+            .insert(&room.id)
+            .expect("Failed to record playing room");
+        self.record_frame(&board).await;
+        self.save_board(board);
+        self.save_room(room);
+    }
+
+    /// Submits the caller's move for a tick of their room, resolving the tick once
+    /// every living player has moved
+    async fn submit_move(&mut self, tick: u64, direction: state::Direction) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        let room = self.current_room(&player).await;
+        assert_eq!(room.status, RoomStatus::Playing, "The room isn't playing");
+        let mut board = self.get_board(room.id).await;
+        assert!(
+            !board.commit_reveal,
+            "Moves in this room must be committed and revealed"
+        );
+        assert_eq!(
+            tick,
+            board.tick + 1,
+            "Moves are being collected for another tick"
+        );
+
+        let snake = board
+            .snakes
+            .iter_mut()
+            .find(|snake| snake.player == player)
+            .expect("Player has no snake on the board");
+        assert!(snake.alive, "The player's snake is out");
+        assert!(
+            snake.next_direction.is_none(),
+            "A move was already submitted for this tick"
+        );
+        snake.next_direction = Some(direction);
+
+        if board.pending_players().is_empty() {
+            self.resolve_tick(room, &mut board).await;
+        }
+        self.save_board(board);
+    }
+
+    /// Commits the caller to a hidden move for a tick of their commit-reveal room,
+    /// opening the reveals once every living player has committed
+    async fn commit_move(&mut self, tick: u64, commitment: CryptoHash) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        let room = self.current_room(&player).await;
+        assert_eq!(room.status, RoomStatus::Playing, "The room isn't playing");
+        let mut board = self.get_board(room.id).await;
+        assert!(
+            board.commit_reveal,
+            "Moves in this room are submitted directly"
+        );
+        assert_eq!(
+            board.phase,
+            TickPhase::Submit,
+            "Committed moves are being revealed"
+        );
+        assert_eq!(
+            tick,
+            board.tick + 1,
+            "Moves are being collected for another tick"
+        );
+
+        let snake = board
+            .snakes
+            .iter_mut()
+            .find(|snake| snake.player == player)
+            .expect("Player has no snake on the board");
+        assert!(snake.alive, "The player's snake is out");
+        assert!(
+            snake.commitment.is_none(),
+            "A move was already committed for this tick"
+        );
+        snake.commitment = Some(commitment);
+
+        if board.pending_players().is_empty() {
+            self.open_reveals(room, &mut board).await;
+        }
+        self.save_board(board);
+    }
+
+    /// Reveals the move the caller committed to, resolving the tick once every
+    /// committed move is revealed
+    async fn reveal_move(&mut self, tick: u64, direction: state::Direction, salt: String) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        let room = self.current_room(&player).await;
+        assert_eq!(room.status, RoomStatus::Playing, "The room isn't playing");
+        let mut board = self.get_board(room.id).await;
+        assert_eq!(
+            board.phase,
+            TickPhase::Reveal,
+            "Moves aren't being revealed"
+        );
+        assert_eq!(
+            tick,
+            board.tick + 1,
+            "Moves are being revealed for another tick"
+        );
+
+        let snake = board
+            .snakes
+            .iter_mut()
+            .find(|snake| snake.player == player)
+            .expect("Player has no snake on the board");
+        let commitment = snake
+            .commitment
+            .expect("No move was committed for this tick");
+        assert!(
+            snake.next_direction.is_none(),
+            "The move was already revealed"
+        );
+        let revealed = MoveCommitment {
+            player,
+            tick,
+            direction,
+            salt,
+        };
+        assert_eq!(
+            revealed.hash(),
+            commitment,
+            "The move doesn't match its commitment"
+        );
+        snake.next_direction = Some(direction);
+
+        if board.pending_players().is_empty() {
+            self.resolve_tick(room, &mut board).await;
+        }
+        self.save_board(board);
+    }
+
+    /// Ends the current phase of a room's tick once its deadline has passed: missing
+    /// commitments give way to the reveals, and missing moves or reveals leave those
+    /// snakes carrying on straight. Anyone may call it
+    async fn resolve_tick_after_deadline(&mut self, room_id: u64) {
+        let room = self.get_room(room_id).await;
+        assert_eq!(room.status, RoomStatus::Playing, "The room isn't playing");
+        let mut board = self.get_board(room_id).await;
+        assert!(
+            self.runtime.system_time() > board.tick_deadline,
+            "The tick is still open for moves"
+        );
+
+        // A wagered game doesn't go ahead without everyone, so stakes go back
+        if room.stake > 0
+            && board.tick == 0
+            && board.phase == TickPhase::Submit
+            && !board.pending_players().is_empty()
+        {
+            self.cancel_room(room).await;
+            return;
+        }
+
+        if board.commit_reveal && board.phase == TickPhase::Submit {
+            self.open_reveals(room, &mut board).await;
+        } else {
+            self.resolve_tick(room, &mut board).await;
+        }
+        self.save_board(board);
+    }
+
+    /// Starts revealing the tick's committed moves, or resolves the tick straight away
+    /// if nobody committed
+    async fn open_reveals(&mut self, room: Room, board: &mut state::Board) {
+        board.phase = TickPhase::Reveal;
+        if board.pending_players().is_empty() {
+            self.resolve_tick(room, board).await;
+            return;
+        }
+        let reveal_duration = self.state.room_config.get().reveal_duration;
+        board.tick_deadline = self.runtime.system_time().saturating_add(reveal_duration);
+    }
+
+    /// Moves every snake with the submitted moves and opens the next tick, finishing
+    /// the room if the game is decided. Committed moves that were never revealed count
+    /// as carrying on straight, and cost their players the reveal penalty
+    async fn resolve_tick(&mut self, room: Room, board: &mut state::Board) {
+        let penalty = self.state.room_config.get().missed_reveal_penalty;
+        for snake in &mut board.snakes {
+            if snake.commitment.take().is_some() && snake.next_direction.is_none() {
+                snake.missed_reveals += 1;
+                snake.score = snake.score.saturating_sub(penalty);
+            }
+        }
+        board.phase = TickPhase::Submit;
+
+        let moves = board
+            .snakes
+            .iter_mut()
+            .map(|snake| snake.next_direction.take())
+            .collect::<Vec<_>>();
+        rules::step_board(board, &moves);
+        board.tick_deadline = self.next_tick_deadline();
+        self.record_frame(board).await;
+
+        if rules::is_board_over(board) {
+            self.finish_room(room, board).await;
+        }
+    }
+
+    /// When a tick opened now stops waiting for moves
+    fn next_tick_deadline(&mut self) -> Timestamp {
+        let tick_duration = self.state.room_config.get().tick_duration;
+        self.runtime.system_time().saturating_add(tick_duration)
+    }
+
+    fn set_room_config(&mut self, config: RoomConfig) {
+        self.assert_admin();
+        assert!(config.rake_bps <= 10_000, "Rake can't exceed the whole pot");
+        self.state.room_config.set(config);
+    }
+
+    /// Records the standings of a room whose game is over, pays out its pot and frees
+    /// its players
+    async fn finish_room(&mut self, mut room: Room, board: &state::Board) {
+        room.status = RoomStatus::Finished;
+        room.results = rules::room_results(board);
+        self.pay_out_pot(&mut room).await;
+        self.free_room_members(&room);
+        self.save_room(room);
+    }
+
+    /// Calls off a room whose game never got going, returning any stakes
+    async fn cancel_room(&mut self, mut room: Room) {
+        room.status = RoomStatus::Cancelled;
+        if let Some(mut escrow) = self.held_escrow(room.id).await {
+            for player in &escrow.players {
+                self.credit_points(*player, escrow.stake, LedgerReason::stake_refund(room.id))
+                    .await;
+            }
+            escrow.status = EscrowStatus::Refunded;
+            self.save_escrow(escrow);
+        }
+        self.free_room_members(&room);
+        self.save_room(room);
+    }
+
+    /// Keeps a room's latest board for spectators, dropping the oldest beyond the limit
+    async fn record_frame(&mut self, board: &state::Board) {
+        let mut frames = self
+            .state
+            .room_frames
+            .get(&board.room_id)
+            .await
+            .expect("Failed to read room frames")
+            .unwrap_or_default();
+        frames.push(board.clone());
+        if frames.len() > SPECTATOR_FRAMES {
+            frames.remove(0);
+        }
+        self.state
+            .room_frames
+            .insert(&board.room_id, frames)
+            .expect("Failed to record room frame");
+    }
+
+    /// Frees the players of a room that's no longer being played
+    fn free_room_members(&mut self, room: &Room) {
+        self.state
+            .playing_rooms
+            .remove(&room.id)
+            .expect("Failed to clear playing room");
+        for member in &room.members {
+            self.state
+                .player_rooms
+                .remove(&member.player)
+                .expect("Failed to clear player room");
+        }
+    }
+
+    /// Pays collected rake out to the admin's points
+    async fn withdraw_rake(&mut self, amount: u64) {
+        self.assert_admin();
+        let admin = self
+            .runtime
+            .authenticated_signer()
+            .expect("Admin must be authenticated");
+
+        let collected = *self.state.collected_rake.get();
+        assert!(amount <= collected, "Not that much rake has been collected");
+        self.state.collected_rake.set(collected - amount);
+        self.credit_points(admin, amount, LedgerReason::rake_withdrawal())
+            .await;
+    }
+
+    /// Moves every player's stake from their points into the room's pot
+    async fn lock_stakes(&mut self, room_id: u64, stake: u64, players: &[AccountOwner]) {
+        for player in players {
+            assert!(
+                self.debit_points(*player, stake, LedgerReason::stake(room_id))
+                    .await,
+                "Every player needs {stake} points for the room's stake"
+            );
+        }
+        let escrow = Escrow {
+            room_id,
+            stake,
+            players: players.to_vec(),
+            pot: stake * players.len() as u64,
+            rake_bps: self.state.room_config.get().rake_bps,
+            rake: 0,
+            status: EscrowStatus::Held,
+        };
+        self.save_escrow(escrow);
+    }
+
+    /// Pays a finished room's pot, less the rake, to its winner, or splits it between
+    /// the players who tied for first. What doesn't split evenly goes to the rake
+    async fn pay_out_pot(&mut self, room: &mut Room) {
+        let Some(mut escrow) = self.held_escrow(room.id).await else {
+            return;
+        };
+
+        let rake = (escrow.pot as u128 * escrow.rake_bps as u128 / 10_000) as u64;
+        let prize = escrow.pot - rake;
+        let winners = room
+            .results
+            .iter()
+            .filter(|result| result.placement == 1)
+            .count() as u64;
+        let share = prize / winners;
+
+        for result in &mut room.results {
+            if result.placement == 1 {
+                result.payout = share;
+                self.credit_points(result.player, share, LedgerReason::winnings(room.id))
+                    .await;
+            }
+        }
+
+        escrow.rake = escrow.pot - share * winners;
+        escrow.status = EscrowStatus::PaidOut;
+        let collected = *self.state.collected_rake.get();
+        self.state.collected_rake.set(collected + escrow.rake);
+        self.save_escrow(escrow);
+    }
+
+    /// A room's pot, if it's wagered and still being played for
+    async fn held_escrow(&self, room_id: u64) -> Option<Escrow> {
+        self.state
+            .room_escrows
+            .get(&room_id)
+            .await
+            .expect("Failed to read escrow")
+            .filter(|escrow| escrow.status == EscrowStatus::Held)
+    }
+
+    fn save_escrow(&mut self, escrow: Escrow) {
+        let room_id = escrow.room_id;
+        self.state
+            .room_escrows
+            .insert(&room_id, escrow)
+            .expect("Failed to update escrow");
+    }
+
+    /// Reads a room's board, rejecting rooms that haven't started
+    async fn get_board(&self, room_id: u64) -> state::Board {
+        self.state
+            .room_boards
+            .get(&room_id)
+            .await
+            .expect("Failed to read board")
+            .expect("The room hasn't started")
+    }
+
+    /// Reads a room, rejecting unknown ids
+    async fn get_room(&self, room_id: u64) -> Room {
+        self.state
+            .rooms
+            .get(&room_id)
+            .await
+            .expect("Failed to read room")
+            .expect("Room not found")
+    }
+
+    /// The room the player is in
+    async fn current_room(&self, player: &AccountOwner) -> Room {
+        let room_id = self
+            .state
+            .player_rooms
+            .get(player)
+            .await
+            .expect("Failed to read player room")
+            .expect("Player is not in a room");
+        self.get_room(room_id).await
+    }
+
+    async fn assert_not_in_room(&self, player: &AccountOwner) {
+        assert!(
+            !self
+                .state
+                .player_rooms
+                .contains_key(player)
+                .await
+                .expect("Failed to read player room")
+                && !self
+                    .state
+                    .remote_room_hosts
+                    .contains_key(player)
+                    .await
+                    .expect("Failed to read remote room host"),
+            "Player is already in a room"
+        );
+    }
+
+    /// Joins a room hosted on another chain. The caller's room operations on this chain
+    /// are forwarded there until they leave or the room's game ends
+    async fn join_remote_room(&mut self, host_chain: ChainId, code: String) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        assert_ne!(
+            host_chain,
+            self.runtime.chain_id(),
+            "Rooms on this chain are joined directly"
+        );
+        self.assert_not_in_room(&player).await;
+
+        self.state
+            .remote_room_hosts
+            .insert(&player, host_chain)
+            .expect("Failed to record remote room host");
+        self.state
+            .remote_rooms
+            .remove(&player)
+            .expect("Failed to clear remote room");
+        self.send_room_action(host_chain, RoomAction::Join { code });
+    }
+
+    /// Sends a room operation to the chain hosting the caller's room, if they joined
+    /// it from this chain. Returns whether it was forwarded
+    async fn forward_room_action(&mut self, action: RoomAction) -> bool {
+        let Some(player) = self.runtime.authenticated_signer() else {
+            return false;
+        };
+        let Some(host_chain) = self
+            .state
+            .remote_room_hosts
+            .get(&player)
+            .await
+            .expect("Failed to read remote room host")
+        else {
+            return false;
+        };
+
+        // Even a leave only takes effect here once the host's room update confirms it
+        self.send_room_action(host_chain, action);
+        true
+    }
+
+    /// Sends a room action signed by the caller, tracked so a rejected action bounces
+    fn send_room_action(&mut self, host_chain: ChainId, action: RoomAction) {
+        self.runtime
+            .prepare_message(Message::RoomAction { action })
+            .with_authentication()
+            .with_tracking()
+            .send_to(host_chain);
+    }
+
+    /// Performs a room action a player sent from their own chain, remembering that
+    /// chain so they receive the room's updates
+    async fn perform_room_action(&mut self, player: AccountOwner, action: RoomAction) {
+        self.assert_not_paused();
+        let origin = self
+            .runtime
+            .message_origin_chain_id()
+            .expect("Room actions must come from a chain");
+        self.state
+            .player_chains
+            .insert(&player, origin)
+            .expect("Failed to record player chain");
+
+        match action {
+            RoomAction::Join { code } => self.join_room(code, true).await,
+            RoomAction::Leave => self.leave_room().await,
+            RoomAction::ToggleReady => self.toggle_ready().await,
+            RoomAction::Start => self.start_room().await,
+            RoomAction::SubmitMove { tick, direction } => self.submit_move(tick, direction).await,
+            RoomAction::CommitMove { tick, commitment } => self.commit_move(tick, commitment).await,
+            RoomAction::RevealMove {
+                tick,
+                direction,
+                salt,
+            } => self.reveal_move(tick, direction, salt).await,
+        }
+    }
+
+    /// Records a room's state for the players here who joined it, and frees those who
+    /// left it. Updates from any chain other than the one each player joined on are
+    /// ignored
+    async fn receive_room_update(
+        &mut self,
+        room: Room,
+        board: Option<state::Board>,
+        departed: Vec<AccountOwner>,
+    ) {
+        let host_chain = self
+            .runtime
+            .message_origin_chain_id()
+            .expect("Room updates must come from a chain");
+        let over = matches!(room.status, RoomStatus::Finished | RoomStatus::Cancelled);
+
+        for player in departed {
+            if !self.joined_from_here(&player, host_chain).await {
+                continue;
+            }
+            self.state
+                .remote_room_hosts
+                .remove(&player)
+                .expect("Failed to clear remote room host");
+            self.state
+                .remote_rooms
+                .remove(&player)
+                .expect("Failed to clear remote room");
+        }
+
+        for member in &room.members {
+            if !self.joined_from_here(&member.player, host_chain).await {
+                continue;
+            }
+
+            // Once the game is over the player is free again, with the results kept
+            if over {
+                self.state
+                    .remote_room_hosts
+                    .remove(&member.player)
+                    .expect("Failed to clear remote room host");
+            }
+            self.state
+                .remote_rooms
+                .insert(
+                    &member.player,
+                    RemoteRoom {
+                        host_chain,
+                        room: room.clone(),
+                        board: board.clone(),
+                    },
+                )
+                .expect("Failed to record remote room");
+        }
+    }
+
+    /// Whether the player's room operations here go to the given host chain
+    async fn joined_from_here(&self, player: &AccountOwner, host_chain: ChainId) -> bool {
+        self.state
+            .remote_room_hosts
+            .get(player)
+            .await
+            .expect("Failed to read remote room host")
+            == Some(host_chain)
+    }
+
+    /// Sends the rooms changed by the current operation or message to their members,
+    /// and the players who left them, who play from other chains
+    async fn send_room_updates(&mut self) {
+        for (room_id, departed) in std::mem::take(&mut self.updated_rooms) {
+            let room = self.get_room(room_id).await;
+            let board = self
+                .state
+                .room_boards
+                .get(&room_id)
+                .await
+                .expect("Failed to read board");
+
+            let players = room.members.iter().map(|member| &member.player);
+            let mut chains = BTreeSet::new();
+            for player in players.chain(&departed) {
+                if let Some(chain) = self
+                    .state
+                    .player_chains
+                    .get(player)
+                    .await
+                    .expect("Failed to read player chain")
+                {
+                    chains.insert(chain);
+                }
+            }
+            for chain in chains {
+                self.runtime
+                    .prepare_message(Message::RoomUpdate {
+                        room: room.clone(),
+                        board: board.clone(),
+                        departed: departed.clone(),
+                    })
+                    .send_to(chain);
+            }
+        }
+    }
+
+    fn save_board(&mut self, board: state::Board) {
+        let room_id = board.room_id;
+        self.updated_rooms.entry(room_id).or_default();
+        self.state
+            .room_boards
+            .insert(&room_id, board)
+            .expect("Failed to update board");
+    }
+
+    fn save_room(&mut self, room: Room) {
+        let room_id = room.id;
+        self.updated_rooms.entry(room_id).or_default();
+        self.state
+            .rooms
+            .insert(&room_id, room)
+            .expect("Failed to update room");
+    }
+
+    /// Adds a board players can choose to play on
+    fn create_map(&mut self, name: String, width: i32, height: i32, min_level: u32) {
+        self.assert_admin();
+        assert!(width > 0 && height > 0, "Map dimensions must be positive");
+
+        // Id zero is the default map
+        let id = *self.state.next_map_id.get() + 1;
+        self.state.next_map_id.set(id);
+
+        self.state
+            .maps
+            .insert(
+                &id,
+                GameMap {
+                    id,
+                    name,
+                    width,
+                    height,
+                    min_level,
+                },
+            )
+            .expect("Failed to insert map");
+    }
+
+    /// Reads a map, where the default map follows the current game parameters
+    async fn get_map(&self, map_id: u32) -> GameMap {
+        if map_id == DEFAULT_MAP_ID {
+            return GameMap::classic(*self.state.game_width.get(), *self.state.game_height.get());
+        }
+
+        self.state
+            .maps
+            .get(&map_id)
+            .await
+            .expect("Failed to read map")
+            .expect("Map not found")
+    }
+
+    /// Adds an achievement players can unlock from now on
+    fn define_achievement(&mut self, details: AchievementDetails) {
+        self.assert_admin();
+        assert_eq!(
+            details.kind == AchievementKind::FirstGameOnMap,
+            details.map_id.is_some(),
+            "Map achievements need a map id, and other achievements must not have one"
+        );
+
+        let id = *self.state.next_achievement_id.get();
+        self.state.next_achievement_id.set(id + 1);
+
+        self.state
+            .achievements
+            .insert(
+                &id,
+                Achievement {
+                    id,
+                    name: details.name,
+                    description: details.description,
+                    kind: details.kind,
+                    threshold: details.threshold,
+                    map_id: details.map_id,
+                    reward_points: details.reward_points,
+                    reward_skin: details.reward_skin,
+                },
+            )
+            .expect("Failed to insert achievement");
+    }
+
+    /// Stops an achievement from unlocking; existing unlocks are kept
+    async fn remove_achievement(&mut self, achievement_id: u32) {
+        self.assert_admin();
+        assert!(
+            self.state
+                .achievements
+                .contains_key(&achievement_id)
+                .await
+                .expect("Failed to read achievement"),
+            "Achievement not found"
+        );
+
+        self.state
+            .achievements
+            .remove(&achievement_id)
+            .expect("Failed to remove achievement");
+    }
+
+    /// Unlocks every achievement the player has newly met with this game, paying
+    /// its rewards
+    async fn evaluate_achievements(&mut self, player: AccountOwner, game: &state::GameState) {
+        let games_played = self.games_played(&player).await;
+        let achievements = self
+            .state
+            .achievements
+            .index_values()
+            .await
+            .expect("Failed to read achievements");
+        let now = self.runtime.system_time();
+
+        for (id, achievement) in achievements {
+            if !achievement.is_met(game, games_played) {
+                continue;
+            }
+
+            let unlocked = self
+                .state
+                .unlocked_achievements
+                .load_entry_mut(&player)
+                .await
+                .expect("Failed to load unlocked achievements");
+            if unlocked
+                .contains_key(&id)
+                .await
+                .expect("Failed to read unlocked achievements")
+            {
+                continue;
+            }
+            unlocked
+                .insert(&id, now)
+                .expect("Failed to unlock achievement");
+
+            self.credit_points(
+                player,
+                achievement.reward_points,
+                LedgerReason::achievement(id),
+            )
+            .await;
+            if let Some(skin_id) = achievement.reward_skin {
+                self.grant_skin(player, skin_id).await;
+            }
+        }
+    }
+
+    /// Sets how XP is earned and the XP needed for each level
+    fn set_progression_config(&mut self, config: ProgressionConfig) {
+        self.assert_admin();
+        assert!(
+            config.level_xp.windows(2).all(|pair| pair[0] < pair[1]),
+            "Level XP must be increasing"
+        );
+        self.state.progression.set(config);
+    }
+
+    /// Adds XP to a player, announcing and rewarding every level they reach
+    async fn award_xp(&mut self, player: AccountOwner, amount: u64) {
+        if amount == 0 {
+            return;
+        }
+
+        let xp = self.xp_of(&player).await;
+        self.state
+            .xp
+            .insert(&player, xp + amount)
+            .expect("Failed to update XP");
+
+        let progression = self.state.progression.get().clone();
+        let old_level = progression.level_for(xp);
+        let new_level = progression.level_for(xp + amount);
+        for level in old_level + 1..=new_level {
+            self.runtime.emit(
+                StreamName::from(PROGRESSION_STREAM),
+                &Event::LevelUp { player, level },
+            );
+            for unlock in &progression.level_skins {
+                if unlock.level == level {
+                    self.grant_skin(player, unlock.skin_id).await;
+                }
+            }
+        }
+    }
+
+    /// The XP a player has earned
+    async fn xp_of(&self, player: &AccountOwner) -> u64 {
+        self.state
+            .xp
+            .get(player)
+            .await
+            .expect("Failed to read XP")
+            .unwrap_or(0)
+    }
+
+    /// The level a player is at under the current XP curve
+    async fn level_of(&self, player: &AccountOwner) -> u32 {
+        let xp = self.xp_of(player).await;
+        self.state.progression.get().level_for(xp)
+    }
+
+    /// How many games the player has settled
+    async fn games_played(&self, player: &AccountOwner) -> u64 {
+        self.state
+            .games_played
+            .get(player)
+            .await
+            .expect("Failed to read games played")
+            .unwrap_or(0)
+    }
+
+    /// Grants points to a player's account
+    async fn add_points(&mut self, player: AccountOwner, amount: u64) {
+        self.assert_admin();
+
+        self.credit_points(player, amount, LedgerReason::admin_grant())
+            .await;
+    }
+
+    /// Redeems points from a player's account
+    async fn redeem_points(&mut self, amount: u64) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        self.debit_points(player, amount, LedgerReason::redemption())
+            .await;
+    }
+
+    /// Adds a new reward to the catalog
+    fn create_reward(
+        &mut self,
+        name: String,
+        price: u64,
+        stock: Option<u64>,
+        kind: RewardKind,
+        payout: RewardPayout,
+    ) {
+        self.assert_admin();
+        Self::assert_valid_reward_kind(kind, &payout);
+
+        let id = *self.state.next_reward_id.get();
+        self.state.next_reward_id.set(id + 1);
+
+        self.state
+            .rewards
+            .insert(
+                &id,
+                Reward {
+                    id,
+                    name,
+                    price,
+                    stock,
+                    kind,
+                    skin_id: payout.skin_id,
+                    token_amount: payout.token_amount,
+                    retired: false,
+                },
+            )
+            .expect("Failed to insert reward");
+    }
+
+    /// Replaces the details of an existing reward, keeping its payout
+    async fn update_reward(
+        &mut self,
+        reward_id: u64,
+        name: String,
+        price: u64,
+        stock: Option<u64>,
+        kind: RewardKind,
+    ) {
+        self.assert_admin();
+
+        let mut reward = self.get_reward(reward_id).await;
+        let payout = RewardPayout {
+            skin_id: reward.skin_id,
+            token_amount: reward.token_amount,
+        };
+        Self::assert_valid_reward_kind(kind, &payout);
+        reward.name = name;
+        reward.price = price;
+        reward.stock = stock;
+        reward.kind = kind;
+
+        self.state
+            .rewards
+            .insert(&reward_id, reward)
+            .expect("Failed to update reward");
+    }
+
+    /// Withdraws a reward from redemption
+    async fn retire_reward(&mut self, reward_id: u64) {
+        self.assert_admin();
+
+        let mut reward = self.get_reward(reward_id).await;
+        reward.retired = true;
+
+        self.state
+            .rewards
+            .insert(&reward_id, reward)
+            .expect("Failed to update reward");
+    }
+
+    /// Exchanges points for a catalog reward and adds it to the player's inventory
+    async fn redeem_reward(&mut self, reward_id: u64) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        let mut reward = self.get_reward(reward_id).await;
+        assert!(!reward.retired, "Reward has been retired");
+        if let Some(stock) = reward.stock.as_mut() {
+            assert!(*stock > 0, "Reward is out of stock");
+            *stock -= 1;
+        }
+        if let Some(skin_id) = reward.skin_id {
+            assert!(
+                !self.owns_skin(&player, skin_id).await,
+                "Skin already owned"
+            );
+        }
+
+        assert!(
+            self.debit_points(
+                player,
+                reward.price,
+                LedgerReason::reward_redemption(reward_id)
+            )
+            .await,
+            "Insufficient points"
+        );
+
+        match (reward.skin_id, reward.token_amount) {
+            (Some(skin_id), _) => self.grant_skin(player, skin_id).await,
+            (None, Some(amount)) => self.pay_token_prize(player, amount),
+            (None, None) => {
+                *self
+                    .state
+                    .inventory
+                    .load_entry_mut(&player)
+                    .await
+                    .expect("Failed to load inventory")
+                    .get_mut_or_default(&reward_id)
+                    .await
+                    .expect("Failed to update inventory") += 1;
+            }
+        }
+
+        self.state
+            .rewards
+            .insert(&reward_id, reward)
+            .expect("Failed to update reward");
+    }
+
+    /// Skin and token rewards must say what they grant, and other rewards must not
+    fn assert_valid_reward_kind(kind: RewardKind, payout: &RewardPayout) {
+        assert_eq!(
+            kind == RewardKind::Skin,
+            payout.skin_id.is_some(),
+            "Skin rewards need a skin id, and other rewards must not have one"
+        );
+        assert_eq!(
+            kind == RewardKind::Token,
+            payout.token_amount.is_some(),
+            "Token rewards need a token amount, and other rewards must not have one"
+        );
+    }
+
+    /// Pays the player's daily login reward, growing with their streak
+    async fn claim_daily(&mut self) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        let today = DailyStreak::day_of(self.runtime.system_time());
+        let mut daily = self
+            .state
+            .daily_streaks
+            .get(&player)
+            .await
+            .expect("Failed to read daily streak")
+            .unwrap_or_default();
+
+        assert!(
+            daily.last_claim_day.is_none_or(|day| day < today),
+            "Daily reward already claimed today"
+        );
+
+        daily.streak = daily.current_streak(today) + 1;
+        daily.last_claim_day = Some(today);
+
+        let schedule = self.state.daily_reward_schedule.get();
+        let amount = schedule
+            .get(daily.streak as usize - 1)
+            .or(schedule.last())
+            .copied()
+            .unwrap_or(0);
+
+        self.credit_points(player, amount, LedgerReason::daily_reward())
+            .await;
+        self.state
+            .daily_streaks
+            .insert(&player, daily)
+            .expect("Failed to update daily streak");
+    }
+
+    /// Sets the points paid for each day of a login streak
+    fn set_daily_reward_schedule(&mut self, schedule: Vec<u64>) {
+        self.assert_admin();
+        self.state.daily_reward_schedule.set(schedule);
+    }
+
+    /// Connects or disconnects the fungible-token application
+    fn configure_token(&mut self, config: Option<TokenConfig>) {
+        self.assert_admin();
+        self.state.token.set(config);
+    }
+
+    /// Takes the ranked entry fee from the player into the application's token account
+    fn charge_entry_fee(&mut self, player: AccountOwner) {
+        let fee = self
+            .state
+            .token
+            .get()
+            .as_ref()
+            .expect("Ranked play requires a token application")
+            .ranked_entry_fee;
+
+        if fee > Amount::ZERO {
+            let treasury = self.treasury();
+            self.transfer_tokens(player, fee, treasury);
+        }
+    }
+
+    /// Pays a token prize from the application's token account
+    fn pay_token_prize(&mut self, player: AccountOwner, amount: Amount) {
+        let treasury = self.treasury();
+        self.transfer_tokens(treasury, amount, player);
+    }
+
+    /// The account this application holds tokens in
+    fn treasury(&mut self) -> AccountOwner {
+        self.runtime.application_id().forget_abi().into()
+    }
+
+    /// Moves tokens between two accounts on this chain through the token application
+    fn transfer_tokens(&mut self, owner: AccountOwner, amount: Amount, target: AccountOwner) {
+        let application_id = self
+            .state
+            .token
+            .get()
+            .as_ref()
+            .expect("No token application is configured")
+            .application_id
+            .with_abi::<FungibleTokenAbi>();
+        let target_account = fungible::Account {
+            chain_id: self.runtime.chain_id(),
+            owner: target,
+        };
+
+        self.runtime.call_application(
+            true,
+            application_id,
+            &FungibleOperation::Transfer {
+                owner,
+                amount,
+                target_account,
+            },
+        );
+    }
+
+    /// Adds a skin to the player's collection
+    async fn grant_skin(&mut self, player: AccountOwner, skin_id: u32) {
+        if skin_id == DEFAULT_SKIN_ID {
+            return;
+        }
+
+        self.state
+            .owned_skins
+            .load_entry_mut(&player)
+            .await
+            .expect("Failed to load owned skins")
+            .insert(&skin_id)
+            .expect("Failed to grant skin");
+    }
+
+    /// Whether the player may use the given skin
+    async fn owns_skin(&self, player: &AccountOwner, skin_id: u32) -> bool {
+        if skin_id == DEFAULT_SKIN_ID {
+            return true;
+        }
+
+        match self
+            .state
+            .owned_skins
+            .try_load_entry(player)
+            .await
+            .expect("Failed to load owned skins")
+        {
+            Some(skins) => skins
+                .contains(&skin_id)
+                .await
+                .expect("Failed to read owned skins"),
+            None => false,
+        }
+    }
+
+    /// Selects the skin used by the player's future games
+    async fn equip_skin(&mut self, skin_id: u32) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        assert!(
+            self.owns_skin(&player, skin_id).await,
+            "Skin is not owned by the player"
+        );
+
+        self.state
+            .equipped_skins
+            .insert(&player, skin_id)
+            .expect("Failed to equip skin");
+    }
+
+    /// Reads a catalog reward, rejecting unknown ids
+    async fn get_reward(&self, reward_id: u64) -> Reward {
+        self.state
+            .rewards
+            .get(&reward_id)
+            .await
+            .expect("Failed to read reward")
+            .expect("Reward not found")
+    }
+
+    /// Transfers points to another account, sending a message if it lives on another chain
+    async fn transfer_points(&mut self, to: Account, amount: u64) {
+        let sender = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        assert!(amount > 0, "Transfer amount must be positive");
+
+        let is_local = to.chain_id == self.runtime.chain_id();
+        assert!(
+            !(is_local && to.owner == sender),
+            "Cannot transfer points to yourself"
+        );
+        assert!(
+            self.debit_points(sender, amount, LedgerReason::transfer(to.owner))
+                .await,
+            "Insufficient points"
+        );
+
+        if is_local {
+            self.credit_points(to.owner, amount, LedgerReason::transfer(sender))
+                .await;
+        } else {
+            self.runtime
+                .prepare_message(Message::Credit {
+                    target: to.owner,
+                    amount,
+                })
+                .with_authentication()
+                .with_tracking()
+                .send_to(to.chain_id);
+        }
+    }
+
+    /// Credits points to a player and records the change in their ledger
+    async fn credit_points(&mut self, player: AccountOwner, amount: u64, reason: LedgerReason) {
+        if amount == 0 {
+            return;
+        }
+
+        let balance = self.get_points(&player).await + amount;
+        self.state
+            .points
+            .insert(&player, balance)
+            .expect("Failed to update points");
+        self.record_ledger_entry(player, LedgerEntryKind::Credit, reason, amount, balance)
+            .await;
+    }
+
+    /// Debits points from a player and records the change in their ledger
+    ///
+    /// Returns `false` and leaves the balance untouched if the player can't afford it
+    async fn debit_points(
+        &mut self,
+        player: AccountOwner,
+        amount: u64,
+        reason: LedgerReason,
+    ) -> bool {
+        let current_points = self.get_points(&player).await;
+        if current_points < amount {
+            return false;
+        }
+        if amount == 0 {
+            return true;
+        }
+
+        let balance = current_points - amount;
+        self.state
+            .points
+            .insert(&player, balance)
+            .expect("Failed to update points");
+        self.record_ledger_entry(player, LedgerEntryKind::Debit, reason, amount, balance)
+            .await;
+        true
+    }
+
+    /// Appends an entry to a player's points ledger
+    async fn record_ledger_entry(
+        &mut self,
+        player: AccountOwner,
+        kind: LedgerEntryKind,
+        reason: LedgerReason,
+        amount: u64,
+        balance: u64,
+    ) {
+        let timestamp = self.runtime.system_time();
+        self.state
+            .points_history
+            .load_entry_mut(&player)
+            .await
+            .expect("Failed to load points history")
+            .push(LedgerEntry {
+                kind,
+                reason,
+                amount,
+                balance,
+                timestamp,
+            });
+    }
+
+    /// Sets the game board dimensions
+    async fn set_game_parameters(&mut self, width: i32, height: i32) {
+        self.state.game_width.set(width);
+        self.state.game_height.set(height);
+        self.bump_config_version();
+    }
+
+    /// Updates the food spawn rate
+    async fn update_food_spawn_rate(&mut self, rate: u64) {
+        self.state.food_spawn_rate.set(rate);
+        self.bump_config_version();
+    }
+
+    /// Marks games started from now on as played under new settings
+    fn bump_config_version(&mut self) {
+        let version = *self.state.config_version.get();
+        self.state.config_version.set(version + 1);
+    }
+
+    /// Resets the leaderboard (clears all high scores)
+    async fn reset_leaderboard(&mut self) {
+        // Note: In a production system, you'd iterate and clear all entries
+        // For now, this is a placeholder that would need proper iteration
+        // over the MapView to clear all entries
+    }
+
+    /// Pauses the current game
+    async fn pause_game(&mut self) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        let mut game = self
+            .state
+            .games
+            .get(&player)
+            .await
+            .expect("Failed to read game state")
+            .expect("Game not found");
+
+        if game.status == GameStatus::Active {
+            let now = self.runtime.system_time();
+            game.status = GameStatus::Paused;
+            game.paused_at = Some(now);
+            game.last_active_at = now;
+            self.state
+                .games
+                .insert(&player, game)
+                .expect("Failed to update game state");
+        }
+    }
+
+    /// Resumes a paused game
+    async fn resume_game(&mut self) {
+        let player = self
+            .runtime
+            .authenticated_signer()
+            .expect("Player must be authenticated");
+
+        let mut game = self
+            .state
+            .games
+            .get(&player)
+            .await
+            .expect("Failed to read game state")
+            .expect("Game not found");
+
+        if game.status == GameStatus::Paused {
+            let now = self.runtime.system_time();
+            let expiry = self.state.expiry_config.get().clone();
+            let revive_window = self.state.revive_config.get().window;
+            if self.end_overlong_game(player, &mut game).await {
+                // Past the session limit; the game is over instead
+            } else if game.is_stale(now, &expiry, revive_window) {
+                // Paused for too long; the game is over instead
+                self.mark_over(&mut game, EndCause::Expired);
+                self.settle_game(player, &mut game).await;
+            } else {
+                game.status = GameStatus::Active;
+                game.paused_at = None;
+                game.last_active_at = now;
+            }
+            self.state
+                .games
+                .insert(&player, game)
+                .expect("Failed to update game state");
+        }
+    }
+
+    /// Freezes gameplay and points operations until an admin lifts the pause
+    fn emergency_pause(&mut self, reason: String) {
+        self.assert_admin();
+        self.state.paused.set(true);
+        self.state.pause_reason.set(Some(reason));
+    }
+
+    /// Lifts an emergency pause
+    fn lift_emergency_pause(&mut self) {
+        self.assert_admin();
+        self.state.paused.set(false);
+        self.state.pause_reason.set(None);
+    }
+
+    /// Rejects the operation unless it was signed by the application admin
+    fn assert_admin(&mut self) {
+        let signer = self.runtime.authenticated_signer();
+        assert!(
+            signer.is_some() && signer == *self.state.admin.get(),
+            "Only the application admin can perform this operation"
+        );
+    }
+
+    /// Rejects the operation while the application is under an emergency pause
+    fn assert_not_paused(&self) {
+        if *self.state.paused.get() {
+            panic!(
+                "Application is paused: {}",
+                self.state
+                    .pause_reason
+                    .get()
+                    .as_deref()
+                    .unwrap_or("no reason given")
+            );
+        }
+    }
+
+    /// Gets the game state for a player (helper for queries)
+    pub async fn get_game_state(&self, player: &linera_sdk::linera_base_types::AccountOwner) -> Option<state::GameState> {
+        self.state
+            .games
+            .get(player)
+            .await
+            .expect("Failed to read game state")
+    }
+
+    /// Gets the high score for a player
+    pub async fn get_high_score(&self, player: &linera_sdk::linera_base_types::AccountOwner) -> u64 {
+        self.state
+            .high_scores
+            .get(player)
+            .await
+            .expect("Failed to read high score")
+            .unwrap_or(0)
+    }
+
+    /// Gets points for a player
+    pub async fn get_points(&self, player: &linera_sdk::linera_base_types::AccountOwner) -> u64 {
+        self.state
+            .points
+            .get(player)
+            .await
+            .expect("Failed to read points")
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::let_unit_value)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use futures::FutureExt as _;
+    use linera_sdk::{
+        linera_base_types::Timestamp, util::BlockingWait, views::View, Contract, ContractRuntime,
+    };
+
+    use jeteeah::Operation;
+
+    use jeteeah::state::{EndCause, GameStatus};
+
+    use super::{JeteeahContract, JeteeahState};
+
+    #[test]
+    fn operation() {
+        let initial_value = 10u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let increment = 10u64;
+
+        let _response = app
+            .execute_operation(Operation::Increment { value: increment })
+            .now_or_never()
+            .expect("Execution of application operation should not await anything");
+
+        assert_eq!(*app.state.value.get(), initial_value + increment);
+    }
+
+    #[test]
+    fn test_start_game() {
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        // Set up authentication for testing
+        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(test_owner));
+
+        // Start a new game
+        let _response = app
+            .execute_operation(Operation::StartGame)
+            .blocking_wait();
+
+        // Get the test owner
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+
+        let game_state = app
+            .state
+            .games
+            .get(&player)
+            .blocking_wait()
+            .expect("Failed to read game state")
+            .expect("Game state should exist");
+
+        // Verify initial snake position and length
+        assert_eq!(
+            game_state.snake_body.len(),
+            3,
+            "Snake should start with 3 segments"
+        );
+        assert_eq!(
+            game_state.status,
+            GameStatus::Active,
+            "Game should be active"
+        );
+        assert_eq!(game_state.score, 0, "Initial score should be 0");
+
+        // Verify game board dimensions
+        assert_eq!(game_state.width, 20);
+        assert_eq!(game_state.height, 20);
+
+        // Verify snake is in center area
+        let head = &game_state.snake_body[0];
+        assert_eq!(head.x, 10); // center x
+        assert_eq!(head.y, 10); // center y
+    }
+
+    #[test]
+    fn test_move_snake() {
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+        use jeteeah::state::Direction;
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(test_owner));
+
+        // Start game
+        app.execute_operation(Operation::StartGame)
+            .blocking_wait();
+
+        // Move right
+        app.execute_operation(Operation::MoveSnake { direction: Direction::Right })
+            .blocking_wait();
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        let game_state = app.state.games.get(&player).blocking_wait()
+            .expect("Failed to read").expect("Game should exist");
+
+        // Snake should have moved right
+        assert_eq!(game_state.snake_body[0].x, 11);
+        assert_eq!(game_state.status, GameStatus::Active);
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(test_owner));
+
+        // Start game
+        app.execute_operation(Operation::StartGame)
+            .blocking_wait();
+
+        // Pause game
+        app.execute_operation(Operation::PauseGame)
+            .now_or_never()
+            .expect("Pause game should not await");
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        let game_state = app.state.games.get(&player).blocking_wait()
+            .expect("Failed to read").expect("Game should exist");
+
+        assert_eq!(
+            game_state.status,
+            GameStatus::Paused,
+            "Game should be paused"
+        );
+
+        // Resume game
+        app.execute_operation(Operation::ResumeGame)
+            .now_or_never()
+            .expect("Resume game should not await");
+
+        let game_state = app.state.games.get(&player).blocking_wait()
+            .expect("Failed to read").expect("Game should exist");
+
+        assert_eq!(
+            game_state.status,
+            GameStatus::Active,
+            "Game should be active"
+        );
+    }
+
+    #[test]
+    fn test_end_game_awards_points() {
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(test_owner));
+
+        // Start game
+        app.execute_operation(Operation::StartGame)
+            .blocking_wait();
+
+        // End game
+        app.execute_operation(Operation::EndGame)
+            .blocking_wait();
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        let game_state = app.state.games.get(&player).blocking_wait()
+            .expect("Failed to read").expect("Game should exist");
+
+        assert_eq!(
+            game_state.status,
+            GameStatus::Settled,
+            "Game should be settled"
+        );
+
+        // Check points were awarded (score was 0, so points should be 0)
+        let points = app.state.points.get(&player).blocking_wait()
+            .expect("Failed to read points").unwrap_or(0);
+        assert_eq!(points, game_state.score, "Points should equal score");
+    }
+
+    #[test]
+    fn test_reset_game() {
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(test_owner));
+
+        // Start game
+        app.execute_operation(Operation::StartGame)
+            .blocking_wait();
+
+        // Reset game
+        app.execute_operation(Operation::ResetGame)
+            .blocking_wait();
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        let game_state = app.state.games.get(&player).blocking_wait()
+            .expect("Failed to read").expect("Game should exist");
+
+        // Should be a fresh game
+        assert_eq!(game_state.score, 0);
+        assert_eq!(game_state.snake_body.len(), 3);
+        assert_eq!(game_state.status, GameStatus::Active);
+    }
+
+    #[test]
+    fn test_add_and_redeem_points() {
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
+
+        // The admin grants points
+        app.execute_operation(Operation::AddPoints {
+            target: test_owner,
+            amount: 100,
+        })
+        .blocking_wait();
+        app.runtime.set_authenticated_signer(Some(test_owner));
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        let points = app.state.points.get(&player).blocking_wait()
+            .expect("Failed to read points").unwrap_or(0);
+        assert_eq!(points, 100);
+
+        // Redeem points
+        app.execute_operation(Operation::RedeemPoints { amount: 50 })
+            .blocking_wait();
+
+        let points = app.state.points.get(&player).blocking_wait()
+            .expect("Failed to read points").unwrap_or(0);
+        assert_eq!(points, 50);
+
+        // Try to redeem more than available (should fail silently)
+        app.execute_operation(Operation::RedeemPoints { amount: 100 })
+            .blocking_wait();
+
+        let points = app.state.points.get(&player).blocking_wait()
+            .expect("Failed to read points").unwrap_or(0);
+        assert_eq!(points, 50, "Points should not change if insufficient");
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the application admin")]
+    fn test_add_points_requires_admin() {
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(test_owner));
+
+        app.execute_operation(Operation::AddPoints {
+            target: test_owner,
+            amount: 100,
+        })
+        .blocking_wait();
+    }
+
+    #[test]
+    fn test_set_game_parameters() {
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        // Set new dimensions
+        app.execute_operation(Operation::SetGameParameters { width: 30, height: 25 })
+            .now_or_never()
+            .expect("Set game parameters should not await");
+
+        assert_eq!(*app.state.game_width.get(), 30);
+        assert_eq!(*app.state.game_height.get(), 25);
+    }
+
+    #[test]
+    fn test_update_food_spawn_rate() {
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        // Update food spawn rate
+        app.execute_operation(Operation::UpdateFoodSpawnRate { rate: 5 })
+            .now_or_never()
+            .expect("Update food spawn rate should not await");
+
+        assert_eq!(*app.state.food_spawn_rate.get(), 5);
+    }
+
+    #[test]
+    fn test_collision_detection_wall() {
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+        use jeteeah::state::Direction;
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(test_owner));
+
+        // Start game
+        app.execute_operation(Operation::StartGame)
+            .blocking_wait();
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        
+        // Snake starts at x=10, y=10. Move Up 11 times to reach y=-1 (collision with top wall)
+        for _ in 0..11 {
+            app.execute_operation(Operation::MoveSnake { direction: Direction::Up })
+                .blocking_wait();
+        }
+
+        let game_state = app.state.games.get(&player).blocking_wait()
+            .expect("Failed to read").expect("Game should exist");
+        
+        // Check that collision was detected
+        assert_eq!(
+            game_state.status,
+            GameStatus::Settled,
+            "Game should be over after wall collision. Final y position: {}",
+            game_state.snake_body[0].y
+        );
+        assert_eq!(game_state.end_cause, Some(EndCause::WallCollision));
+    }
+
+    #[test]
+    fn test_emergency_pause_blocks_gameplay() {
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        // The instantiating signer is the admin
+        app.execute_operation(Operation::EmergencyPause {
+            reason: "Investigating exploit".to_string(),
+        })
+        .blocking_wait();
+
+        assert!(*app.state.paused.get());
+        assert_eq!(
+            app.state.pause_reason.get().as_deref(),
+            Some("Investigating exploit")
+        );
+
+        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(test_owner));
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            app.execute_operation(Operation::StartGame).blocking_wait()
+        }));
+        assert!(result.is_err(), "Gameplay should be rejected while paused");
+
+        // Admin lifts the pause and gameplay resumes
+        app.runtime.set_authenticated_signer(Some(admin_owner()));
+        app.execute_operation(Operation::LiftEmergencyPause)
+            .blocking_wait();
+        assert!(!*app.state.paused.get());
+
+        app.runtime.set_authenticated_signer(Some(test_owner));
+        app.execute_operation(Operation::StartGame)
+            .blocking_wait();
+        assert!(app
+            .state
+            .games
+            .get(&test_owner)
+            .blocking_wait()
+            .expect("Failed to read")
+            .is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the application admin")]
+    fn test_emergency_pause_requires_admin() {
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(test_owner));
+
+        app.execute_operation(Operation::EmergencyPause {
+            reason: "Not allowed".to_string(),
+        })
+        .blocking_wait();
+    }
+
+    #[test]
+    fn test_points_ledger_records_changes() {
+        use jeteeah::state::{LedgerEntryKind, LedgerReasonKind};
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(test_owner));
+
+        app.runtime.set_system_time(Timestamp::from(1_000));
+        grant_points(&mut app, test_owner, 100);
+
+        app.runtime.set_system_time(Timestamp::from(2_000));
+        app.execute_operation(Operation::RedeemPoints { amount: 30 })
+            .blocking_wait();
+
+        // Failed redemptions leave no trace
+        app.execute_operation(Operation::RedeemPoints { amount: 500 })
+            .blocking_wait();
+
+        let history = app
+            .state
+            .points_history
+            .try_load_entry(&test_owner)
+            .blocking_wait()
+            .expect("Failed to load history")
+            .expect("History should exist");
+        let entries = history.read(..).blocking_wait().expect("Failed to read");
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind, LedgerEntryKind::Credit);
+        assert_eq!(entries[0].reason.kind, LedgerReasonKind::AdminGrant);
+        assert_eq!(entries[0].amount, 100);
+        assert_eq!(entries[0].balance, 100);
+        assert_eq!(entries[0].timestamp, Timestamp::from(1_000));
+        assert_eq!(entries[1].kind, LedgerEntryKind::Debit);
+        assert_eq!(entries[1].reason.kind, LedgerReasonKind::Redemption);
+        assert_eq!(entries[1].amount, 30);
+        assert_eq!(entries[1].balance, 70);
+        assert_eq!(entries[1].timestamp, Timestamp::from(2_000));
+    }
+
+    #[test]
+    fn test_transfer_points_on_same_chain() {
+        use linera_sdk::linera_base_types::{Account, AccountOwner, ChainId, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let chain_id = ChainId(CryptoHash::from([7u8; 32]));
+        let sender = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        let recipient = AccountOwner::from(CryptoHash::from([2u8; 32]));
+        app.runtime.set_chain_id(chain_id);
+        app.runtime.set_authenticated_signer(Some(sender));
+
+        grant_points(&mut app, sender, 100);
+        app.execute_operation(Operation::TransferPoints {
+            to: Account {
+                chain_id,
+                owner: recipient,
+            },
+            amount: 40,
+        })
+        .blocking_wait();
+
+        assert_eq!(app.get_points(&sender).blocking_wait(), 60);
+        assert_eq!(app.get_points(&recipient).blocking_wait(), 40);
+        assert!(app.runtime.created_send_message_requests().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient points")]
+    fn test_transfer_points_requires_balance() {
+        use linera_sdk::linera_base_types::{Account, AccountOwner, ChainId, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let chain_id = ChainId(CryptoHash::from([7u8; 32]));
+        app.runtime.set_chain_id(chain_id);
+        app.runtime
+            .set_authenticated_signer(Some(AccountOwner::from(CryptoHash::from([1u8; 32]))));
+
+        app.execute_operation(Operation::TransferPoints {
+            to: Account {
+                chain_id,
+                owner: AccountOwner::from(CryptoHash::from([2u8; 32])),
+            },
+            amount: 1,
+        })
+        .blocking_wait();
+    }
+
+    #[test]
+    fn test_cross_chain_transfer_and_bounce_refund() {
+        use jeteeah::Message;
+        use linera_sdk::linera_base_types::{Account, AccountOwner, ChainId, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let sender = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        let recipient = AccountOwner::from(CryptoHash::from([2u8; 32]));
+        let other_chain = ChainId(CryptoHash::from([8u8; 32]));
+        app.runtime.set_chain_id(ChainId(CryptoHash::from([7u8; 32])));
+        app.runtime.set_authenticated_signer(Some(sender));
+
+        grant_points(&mut app, sender, 100);
+        app.execute_operation(Operation::TransferPoints {
+            to: Account {
+                chain_id: other_chain,
+                owner: recipient,
+            },
+            amount: 25,
+        })
+        .blocking_wait();
+
+        // The sender is debited right away and a tracked message goes out
+        assert_eq!(app.get_points(&sender).blocking_wait(), 75);
+        {
+            let messages = app.runtime.created_send_message_requests();
+            assert_eq!(messages.len(), 1);
+            assert_eq!(messages[0].destination, other_chain);
+            assert!(messages[0].is_tracked);
+            assert!(matches!(
+                messages[0].message,
+                Message::Credit { target, amount: 25 } if target == recipient
+            ));
+        }
+
+        // The message bounces back and the sender is refunded
+        app.runtime.set_message_is_bouncing(Some(true));
+        app.execute_message(Message::Credit {
+            target: recipient,
+            amount: 25,
+        })
+        .blocking_wait();
+
+        assert_eq!(app.get_points(&sender).blocking_wait(), 100);
+        assert_eq!(app.get_points(&recipient).blocking_wait(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Application is paused")]
+    fn test_incoming_credits_rejected_while_paused() {
+        use jeteeah::Message;
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        app.execute_operation(Operation::EmergencyPause {
+            reason: "Investigating".to_string(),
+        })
+        .blocking_wait();
+
+        app.runtime
+            .set_authenticated_signer(Some(AccountOwner::from(CryptoHash::from([1u8; 32]))));
+        app.runtime.set_message_is_bouncing(Some(false));
+        app.execute_message(Message::Credit {
+            target: AccountOwner::from(CryptoHash::from([2u8; 32])),
+            amount: 25,
+        })
+        .blocking_wait();
+    }
+
+    #[test]
+    fn test_redeem_reward_from_catalog() {
+        use jeteeah::state::RewardKind;
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        app.execute_operation(Operation::CreateReward {
+            name: "Golden Apple".to_string(),
+            price: 60,
+            stock: Some(1),
+            kind: RewardKind::Consumable,
+            payout: None,
+        })
+        .blocking_wait();
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(player));
+        grant_points(&mut app, player, 100);
+        app.execute_operation(Operation::RedeemReward { reward_id: 0 })
+            .blocking_wait();
+
+        assert_eq!(app.get_points(&player).blocking_wait(), 40);
+        let reward = app.get_reward(0).blocking_wait();
+        assert_eq!(reward.stock, Some(0));
+        let quantity = app
+            .state
+            .inventory
+            .try_load_entry(&player)
+            .blocking_wait()
+            .expect("Failed to load inventory")
+            .expect("Inventory should exist")
+            .get(&0)
+            .blocking_wait()
+            .expect("Failed to read inventory");
+        assert_eq!(quantity, Some(1));
+
+        // The last unit is gone
+        grant_points(&mut app, player, 100);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            app.execute_operation(Operation::RedeemReward { reward_id: 0 })
+                .blocking_wait()
+        }));
+        assert!(result.is_err(), "Out of stock rewards can't be redeemed");
+    }
+
+    #[test]
+    #[should_panic(expected = "Reward has been retired")]
+    fn test_retired_reward_cannot_be_redeemed() {
+        use jeteeah::state::RewardKind;
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        app.execute_operation(Operation::CreateReward {
+            name: "Sticker".to_string(),
+            price: 0,
+            stock: None,
+            kind: RewardKind::Merchandise,
+            payout: None,
+        })
+        .blocking_wait();
+        app.execute_operation(Operation::RetireReward { reward_id: 0 })
+            .blocking_wait();
+
+        app.runtime
+            .set_authenticated_signer(Some(AccountOwner::from(CryptoHash::from([1u8; 32]))));
+        app.execute_operation(Operation::RedeemReward { reward_id: 0 })
+            .blocking_wait();
+    }
+
+    #[test]
+    fn test_buy_and_equip_skin() {
+        use jeteeah::state::{RewardKind, RewardPayout};
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        app.execute_operation(Operation::CreateReward {
+            name: "Snow White".to_string(),
+            price: 50,
+            stock: None,
+            kind: RewardKind::Skin,
+            payout: Some(RewardPayout {
+                skin_id: Some(1),
+                token_amount: None,
+            }),
+        })
+        .blocking_wait();
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(player));
+        grant_points(&mut app, player, 50);
+        app.execute_operation(Operation::RedeemReward { reward_id: 0 })
+            .blocking_wait();
+        app.execute_operation(Operation::EquipSkin { skin_id: 1 })
+            .blocking_wait();
+        app.execute_operation(Operation::StartGame).blocking_wait();
+
+        assert_eq!(app.get_points(&player).blocking_wait(), 0);
+        let game_state = app
+            .get_game_state(&player)
+            .blocking_wait()
+            .expect("Game should exist");
+        assert_eq!(game_state.skin_id, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Skin is not owned by the player")]
+    fn test_equip_unowned_skin() {
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        app.runtime
+            .set_authenticated_signer(Some(AccountOwner::from(CryptoHash::from([1u8; 32]))));
+        app.execute_operation(Operation::EquipSkin { skin_id: 3 })
+            .blocking_wait();
+    }
+
+    #[test]
+    fn test_ranked_game_charges_entry_fee_and_token_rewards_pay_out() {
+        use std::{cell::RefCell, rc::Rc};
+
+        use jeteeah::state::{RewardKind, RewardPayout, TokenConfig};
+        use linera_sdk::{
+            abis::fungible::{FungibleOperation, FungibleResponse, FungibleTokenAbi},
+            linera_base_types::{
+                AccountOwner, Amount, ApplicationId, ChainId, ContractAbi, CryptoHash,
+            },
+        };
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let recorded_calls = calls.clone();
+        let token_id = ApplicationId::new(CryptoHash::from([5u8; 32]));
+        let jeteeah_id = ApplicationId::new(CryptoHash::from([6u8; 32]));
+        app.runtime
+            .set_chain_id(ChainId(CryptoHash::from([7u8; 32])));
+        app.runtime.set_application_id(jeteeah_id.with_abi());
+        app.runtime
+            .set_call_application_handler(move |authenticated, application_id, bytes| {
+                assert!(authenticated);
+                assert_eq!(application_id, token_id);
+                recorded_calls.borrow_mut().push(
+                    FungibleTokenAbi::deserialize_operation(bytes).expect("Invalid operation"),
+                );
+                FungibleTokenAbi::serialize_response(FungibleResponse::Ok)
+                    .expect("Failed to serialize response")
+            });
+
+        app.execute_operation(Operation::ConfigureToken {
+            config: Some(TokenConfig {
+                application_id: token_id,
+                ranked_entry_fee: Amount::from_tokens(2),
+            }),
+        })
+        .blocking_wait();
+        app.execute_operation(Operation::CreateReward {
+            name: "Token prize".to_string(),
+            price: 10,
+            stock: None,
+            kind: RewardKind::Token,
+            payout: Some(RewardPayout {
+                skin_id: None,
+                token_amount: Some(Amount::from_tokens(5)),
+            }),
+        })
+        .blocking_wait();
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        let treasury = AccountOwner::from(jeteeah_id);
+        app.runtime.set_authenticated_signer(Some(player));
+        app.execute_operation(Operation::StartRankedGame)
+            .blocking_wait();
+        grant_points(&mut app, player, 10);
+        app.execute_operation(Operation::RedeemReward { reward_id: 0 })
+            .blocking_wait();
+
+        assert!(
+            app.get_game_state(&player)
+                .blocking_wait()
+                .expect("Game should exist")
+                .ranked
+        );
+        let calls = calls.borrow();
+        assert_eq!(calls.len(), 2);
+        assert!(matches!(
+            calls[0],
+            FungibleOperation::Transfer { owner, amount, target_account }
+                if owner == player
+                    && amount == Amount::from_tokens(2)
+                    && target_account.owner == treasury
+        ));
+        assert!(matches!(
+            calls[1],
+            FungibleOperation::Transfer { owner, amount, target_account }
+                if owner == treasury
+                    && amount == Amount::from_tokens(5)
+                    && target_account.owner == player
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Ranked play requires a token application")]
+    fn test_ranked_game_requires_token() {
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        app.runtime
+            .set_authenticated_signer(Some(AccountOwner::from(CryptoHash::from([1u8; 32]))));
+        app.execute_operation(Operation::StartRankedGame)
+            .blocking_wait();
+    }
+
+    #[test]
+    fn test_collision_settles_score_exactly_once() {
+        use jeteeah::state::Direction;
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(player));
+        app.execute_operation(Operation::StartGame).blocking_wait();
+
+        // Head starts at (10, 10) and the first food sits at (15, 15)
+        for direction in [Direction::Right; 5]
+            .into_iter()
+            .chain([Direction::Down; 5])
+        {
+            app.execute_operation(Operation::MoveSnake { direction })
+                .blocking_wait();
+        }
+        let game_state = app
+            .get_game_state(&player)
+            .blocking_wait()
+            .expect("Game should exist");
+        assert_eq!(game_state.score, 10);
+        assert_eq!(game_state.status, GameStatus::Active);
+
+        // Crash into the bottom wall
+        for _ in 0..5 {
+            app.execute_operation(Operation::MoveSnake {
+                direction: Direction::Down,
+            })
+            .blocking_wait();
+        }
+        let game_state = app
+            .get_game_state(&player)
+            .blocking_wait()
+            .expect("Game should exist");
+        assert_eq!(game_state.status, GameStatus::Settled);
+        assert_eq!(app.get_points(&player).blocking_wait(), 10);
+
+        // Ending the settled game again pays nothing more
+        app.execute_operation(Operation::EndGame).blocking_wait();
+        app.execute_operation(Operation::EndGame).blocking_wait();
+        assert_eq!(app.get_points(&player).blocking_wait(), 10);
+    }
+
+    #[test]
+    fn test_reset_settles_unfinished_game() {
+        use jeteeah::state::Direction;
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(player));
+        app.execute_operation(Operation::StartGame).blocking_wait();
+        for direction in [Direction::Right; 5]
+            .into_iter()
+            .chain([Direction::Down; 5])
+        {
+            app.execute_operation(Operation::MoveSnake { direction })
+                .blocking_wait();
+        }
+
+        app.execute_operation(Operation::ResetGame).blocking_wait();
+
+        assert_eq!(app.get_points(&player).blocking_wait(), 10);
+        let game_state = app
+            .get_game_state(&player)
+            .blocking_wait()
+            .expect("Game should exist");
+        assert_eq!(game_state.status, GameStatus::Active);
+        assert_eq!(game_state.score, 0);
+    }
+
+    #[test]
+    fn test_daily_claims_build_and_reset_streak() {
+        use jeteeah::state::MICROS_PER_DAY;
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        app.execute_operation(Operation::SetDailyRewardSchedule {
+            schedule: vec![10, 20, 30],
+        })
+        .blocking_wait();
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(player));
+
+        // Claims on four consecutive days, then one after a missed day
+        let mut expected_points = 0;
+        for (day, payout, streak) in [(1, 10, 1), (2, 20, 2), (3, 30, 3), (4, 30, 4), (6, 10, 1)] {
+            app.runtime
+                .set_system_time(Timestamp::from(day * MICROS_PER_DAY + 3_600_000_000));
+            app.execute_operation(Operation::ClaimDaily).blocking_wait();
+
+            expected_points += payout;
+            assert_eq!(app.get_points(&player).blocking_wait(), expected_points);
+            let daily = app
+                .state
+                .daily_streaks
+                .get(&player)
+                .blocking_wait()
+                .expect("Failed to read streak")
+                .expect("Streak should exist");
+            assert_eq!(daily.streak, streak);
+        }
+
+        // A second claim on the same day is rejected
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            app.execute_operation(Operation::ClaimDaily).blocking_wait()
+        }));
+        assert!(result.is_err(), "Only one claim per day is allowed");
+    }
+
+    #[test]
+    fn test_revive_after_collision() {
+        use jeteeah::state::{Direction, ReviveConfig};
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash, TimeDelta};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        app.execute_operation(Operation::SetReviveConfig {
+            config: ReviveConfig {
+                max_revives: 2,
+                window: TimeDelta::from_secs(10),
+                base_cost: 20,
+            },
+        })
+        .blocking_wait();
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(player));
+        grant_points(&mut app, player, 100);
+        app.execute_operation(Operation::StartGame).blocking_wait();
+
+        // Head starts at (10, 10): ten steps up reach the top row, the eleventh crashes
+        let crash = |app: &mut JeteeahContract| {
+            for _ in 0..11 {
+                app.execute_operation(Operation::MoveSnake {
+                    direction: Direction::Up,
+                })
+                .blocking_wait();
+            }
+        };
+        crash(&mut app);
+
+        let game_state = app
+            .get_game_state(&player)
+            .blocking_wait()
+            .expect("Game should exist");
+        assert_eq!(game_state.status, GameStatus::Over);
+        assert_eq!(game_state.snake_body[0].y, 0);
+
+        app.runtime.set_system_time(Timestamp::from(5_000_000));
+        app.execute_operation(Operation::Revive).blocking_wait();
+
+        let game_state = app
+            .get_game_state(&player)
+            .blocking_wait()
+            .expect("Game should exist");
+        assert_eq!(game_state.status, GameStatus::Active);
+        assert_eq!(game_state.revive_count, 1);
+        assert_eq!(app.get_points(&player).blocking_wait(), 80);
+
+        // Turn away from the wall and carry on
+        app.execute_operation(Operation::MoveSnake {
+            direction: Direction::Left,
+        })
+        .blocking_wait();
+        let game_state = app
+            .get_game_state(&player)
+            .blocking_wait()
+            .expect("Game should exist");
+        assert_eq!(game_state.status, GameStatus::Active);
+
+        // The second revive costs double, and the window is enforced
+        app.execute_operation(Operation::MoveSnake {
+            direction: Direction::Up,
+        })
+        .blocking_wait();
+        app.runtime.set_system_time(Timestamp::from(20_000_000));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            app.execute_operation(Operation::Revive).blocking_wait()
+        }));
+        assert!(result.is_err(), "Revives after the window are rejected");
+
+        app.execute_operation(Operation::EndGame).blocking_wait();
+        let game_state = app
+            .get_game_state(&player)
+            .blocking_wait()
+            .expect("Game should exist");
+        assert_eq!(game_state.status, GameStatus::Settled);
+        assert_eq!(game_state.end_cause, Some(EndCause::WallCollision));
+    }
+
+    #[test]
+    fn test_achievements_unlock_and_pay_rewards() {
+        use jeteeah::state::{AchievementDetails, AchievementKind, Direction};
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        app.execute_operation(Operation::CreateMap {
+            name: "Arena".to_string(),
+            width: 30,
+            height: 30,
+            min_level: 1,
+        })
+        .blocking_wait();
+        let achievement = |name: &str, kind, threshold, map_id, reward_points, reward_skin| {
+            Operation::DefineAchievement {
+                details: AchievementDetails {
+                    name: name.to_string(),
+                    description: String::new(),
+                    kind,
+                    threshold,
+                    map_id,
+                    reward_points,
+                    reward_skin,
+                },
+            }
+        };
+        for operation in [
+            achievement("First bite", AchievementKind::Score, 10, None, 25, Some(3)),
+            achievement("Straight", AchievementKind::NoTurnStreak, 5, None, 5, None),
+            achievement("Long", AchievementKind::SnakeLength, 10, None, 100, None),
+            achievement(
+                "Arena",
+                AchievementKind::FirstGameOnMap,
+                0,
+                Some(1),
+                7,
+                None,
+            ),
+        ] {
+            app.execute_operation(operation).blocking_wait();
+        }
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(player));
+        app.runtime.set_system_time(Timestamp::from(1_000));
+        app.execute_operation(Operation::StartGameOnMap { map_id: 1 })
+            .blocking_wait();
+
+        // Head starts at (15, 15) and food at (20, 20)
+        for direction in [[Direction::Right; 5], [Direction::Down; 5]].concat() {
+            app.execute_operation(Operation::MoveSnake { direction })
+                .blocking_wait();
+        }
+        app.execute_operation(Operation::EndGame).blocking_wait();
+
+        let game_state = app
+            .get_game_state(&player)
+            .blocking_wait()
+            .expect("Game should exist");
+        assert_eq!(game_state.map_id, 1);
+        assert_eq!(game_state.width, 30);
+        assert_eq!(game_state.score, 10);
+
+        let unlocked = app
+            .state
+            .unlocked_achievements
+            .try_load_entry(&player)
+            .blocking_wait()
+            .expect("Failed to load unlocked achievements")
+            .expect("Player should have unlocked achievements")
+            .index_values()
+            .blocking_wait()
+            .expect("Failed to read unlocked achievements");
+        assert_eq!(
+            unlocked,
+            vec![
+                (0, Timestamp::from(1_000)),
+                (1, Timestamp::from(1_000)),
+                (3, Timestamp::from(1_000)),
+            ]
+        );
+
+        // Score, plus the three unlocked rewards
+        assert_eq!(app.get_points(&player).blocking_wait(), 10 + 25 + 5 + 7);
+        app.execute_operation(Operation::EquipSkin { skin_id: 3 })
+            .blocking_wait();
+
+        // Unlocks pay out only once
+        app.execute_operation(Operation::StartGameOnMap { map_id: 1 })
+            .blocking_wait();
+        app.execute_operation(Operation::EndGame).blocking_wait();
+        assert_eq!(app.get_points(&player).blocking_wait(), 47);
+    }
+
+    #[test]
+    fn test_xp_levels_unlock_maps_and_skins() {
+        use jeteeah::state::{Direction, LevelSkin, ProgressionConfig};
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+
+        app.execute_operation(Operation::SetProgressionConfig {
+            config: ProgressionConfig {
+                xp_per_move: 1,
+                xp_per_food: 10,
+                xp_per_game: 5,
+                level_xp: vec![10, 20, 100],
+                ranked_level: 4,
+                level_skins: vec![LevelSkin {
+                    level: 3,
+                    skin_id: 7,
+                }],
+            },
+        })
+        .blocking_wait();
+        app.execute_operation(Operation::CreateMap {
+            name: "Veteran".to_string(),
+            width: 30,
+            height: 30,
+            min_level: 2,
+        })
+        .blocking_wait();
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(player));
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            app.execute_operation(Operation::StartGameOnMap { map_id: 1 })
+                .blocking_wait()
+        }));
+        assert!(result.is_err(), "Locked maps can't be played");
+
+        // Head starts at (10, 10) and food at (15, 15): ten moves and one meal
+        app.execute_operation(Operation::StartGame).blocking_wait();
+        for direction in [[Direction::Right; 5], [Direction::Down; 5]].concat() {
+            app.execute_operation(Operation::MoveSnake { direction })
+                .blocking_wait();
+        }
+        app.execute_operation(Operation::EndGame).blocking_wait();
+
+        let xp = app
+            .state
+            .xp
+            .get(&player)
+            .blocking_wait()
+            .expect("Failed to read XP");
+        assert_eq!(xp, Some(10 + 10 + 5));
+        assert_eq!(app.level_of(&player).blocking_wait(), 3);
+        assert!(app.owns_skin(&player, 7).blocking_wait());
+
+        app.execute_operation(Operation::StartGameOnMap { map_id: 1 })
+            .blocking_wait();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            app.execute_operation(Operation::StartRankedGame)
+                .blocking_wait()
+        }));
+        assert!(result.is_err(), "Ranked play needs level 4");
+    }
+
+    #[test]
+    fn test_settled_games_are_archived() {
+        use jeteeah::state::{Direction, GameMode};
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash, TimeDelta};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+        app.execute_operation(Operation::SetGameParameters {
+            width: 30,
+            height: 30,
+        })
+        .blocking_wait();
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(player));
+        app.runtime.set_system_time(Timestamp::from(1_000_000));
+        app.execute_operation(Operation::StartGame).blocking_wait();
+        for _ in 0..3 {
+            app.execute_operation(Operation::MoveSnake {
+                direction: Direction::Up,
+            })
+            .blocking_wait();
+        }
+        app.runtime.set_system_time(Timestamp::from(10_000_000));
+        app.execute_operation(Operation::EndGame).blocking_wait();
+
+        // Replacing an unfinished game archives it too, while a settled one isn't
+        // archived twice
+        app.execute_operation(Operation::StartGame).blocking_wait();
+        app.execute_operation(Operation::StartGame).blocking_wait();
+
+        let history = app
+            .state
+            .game_history
+            .try_load_entry(&player)
+            .blocking_wait()
+            .expect("Failed to load game history")
+            .expect("Player should have a history")
+            .read(0..10)
+            .blocking_wait()
+            .expect("Failed to read game history");
+        assert_eq!(history, vec![0, 1]);
+
+        let summary = app
+            .state
+            .archived_games
+            .get(&0)
+            .blocking_wait()
+            .expect("Failed to read archive")
+            .expect("Game should be archived");
+        assert_eq!(summary.player, player);
+        assert_eq!(summary.steps, 3);
+        assert_eq!(summary.length, 3);
+        assert_eq!(summary.duration, TimeDelta::from_secs(9));
+        assert_eq!(summary.mode, GameMode::Classic);
+        assert_eq!(summary.end_cause, Some(EndCause::Quit));
+        assert_eq!(summary.config_version, 1);
+
+        let inputs = app
+            .state
+            .replay_inputs
+            .try_load_entry(&0)
+            .blocking_wait()
+            .expect("Failed to load replay inputs")
+            .expect("Moves should be recorded")
+            .read(..)
+            .blocking_wait()
+            .expect("Failed to read replay inputs");
+        assert_eq!(inputs, vec![Direction::Up; 3]);
+    }
+
+    #[test]
+    fn test_submitted_runs_are_verified() {
+        use jeteeah::state::{AchievementDetails, AchievementKind, Direction};
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+        app.execute_operation(Operation::SetRunStepLimit { limit: 20 })
+            .blocking_wait();
+        app.execute_operation(Operation::DefineAchievement {
+            details: AchievementDetails {
+                name: "Straight".to_string(),
+                description: String::new(),
+                kind: AchievementKind::NoTurnStreak,
+                threshold: 5,
+                map_id: None,
+                reward_points: 0,
+                reward_skin: None,
+            },
+        })
+        .blocking_wait();
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        app.runtime.set_authenticated_signer(Some(player));
+        app.execute_operation(Operation::StartGame).blocking_wait();
+
+        let submit = |app: &mut JeteeahContract, game_id, inputs: Vec<Direction>| {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                app.execute_operation(Operation::SubmitRun { game_id, inputs })
+                    .blocking_wait()
+            }))
+        };
+
+        // Reversing into itself, playing on after a crash and overlong runs are rejected
+        assert!(submit(&mut app, 0, vec![Direction::Left]).is_err());
+        assert!(submit(&mut app, 0, vec![Direction::Up; 12]).is_err());
+        assert!(submit(&mut app, 0, vec![Direction::Right; 21]).is_err());
+
+        // Eat the food at (15, 15), then run into the right wall
+        let inputs = [
+            [Direction::Right; 5],
+            [Direction::Down; 5],
+            [Direction::Right; 5],
+        ]
+        .concat();
+        assert!(submit(&mut app, 0, inputs.clone()).is_ok());
+
+        let game_state = app
+            .get_game_state(&player)
+            .blocking_wait()
+            .expect("Game should exist");
+        assert_eq!(game_state.status, GameStatus::Settled);
+        assert_eq!(game_state.end_cause, Some(EndCause::WallCollision));
+        assert_eq!(game_state.score, 10);
+        assert_eq!(app.get_points(&player).blocking_wait(), 10);
+        assert_eq!(app.get_high_score(&player).blocking_wait(), 10);
+
+        // The run's streak of five straight moves counts, though it turned afterwards
+        assert!(app
+            .state
+            .unlocked_achievements
+            .try_load_entry(&player)
+            .blocking_wait()
+            .expect("Failed to load unlocked achievements")
+            .expect("Achievements should be unlocked")
+            .contains_key(&0)
+            .blocking_wait()
+            .expect("Failed to read unlocked achievements"));
+
+        // The game's seed is spent once its run is in
+        assert!(submit(&mut app, 0, inputs).is_err());
+    }
+
+    #[test]
+    fn test_ghost_race_challenges() {
+        use jeteeah::state::{ChallengeStatus, Direction, ReviveConfig};
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash, TimeDelta};
+
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+        let players = [1u8, 2].map(|byte| AccountOwner::from(CryptoHash::from([byte; 32])));
+        for player in players {
+            grant_points(&mut app, player, 100);
+        }
+
+        // The challenger's run scores nothing
+        assert!(execute(&mut app, players[0], Operation::StartGame).is_ok());
+        let run = play_run(&mut app, players[0], vec![Direction::Right]);
+        let issue = |game_id| Operation::IssueChallenge {
+            opponent: players[1],
+            game_id,
+            stake: 40,
+        };
+        assert!(execute(&mut app, players[1], issue(run)).is_err());
+        assert!(execute(&mut app, players[0], issue(run)).is_ok());
+        assert_eq!(app.get_points(&players[0]).blocking_wait(), 60);
+
+        // Only the challenger can withdraw it, and they get their stake back
+        let cancel = || Operation::CancelChallenge { challenge_id: 0 };
+        assert!(execute(&mut app, players[1], cancel()).is_err());
+        assert!(execute(&mut app, players[0], cancel()).is_ok());
+        assert_eq!(app.get_points(&players[0]).blocking_wait(), 100);
+
+        // The opponent plays the same seed and beats the run
+        assert!(execute(&mut app, players[0], issue(run)).is_ok());
+        let accept = Operation::AcceptChallenge { challenge_id: 1 };
+        assert!(execute(&mut app, players[1], accept).is_ok());
+        let game = app
+            .get_game_state(&players[1])
+            .blocking_wait()
+            .expect("Game should exist");
+        let challenge = app.get_challenge(1).blocking_wait();
+        assert_eq!(challenge.status, ChallengeStatus::Accepted);
+        assert_eq!(challenge.opponent_game_id, Some(game.id));
+        assert_eq!(game.seed, challenge.seed);
+
+        // Eating the food at (15, 15) is enough to win
+        play_run(
+            &mut app,
+            players[1],
+            [[Direction::Right; 5], [Direction::Down; 5]].concat(),
+        );
 
-        // Snake should have moved right
-        assert_eq!(game_state.snake_body[0].x, 11);
-        assert!(game_state.is_active);
+        let challenge = app.get_challenge(1).blocking_wait();
+        assert_eq!(challenge.status, ChallengeStatus::Completed);
+        assert_eq!(challenge.opponent_score, Some(10));
+        assert_eq!(challenge.winner, Some(players[1]));
+        // The winner takes both stakes on top of the points their game scored
+        assert_eq!(app.get_points(&players[0]).blocking_wait(), 60);
+        assert_eq!(app.get_points(&players[1]).blocking_wait(), 100 + 40 + 10);
+
+        // Challenge games can't be revived, even with revives on, so a crash settles the
+        // challenge straight away
+        let config = ReviveConfig {
+            max_revives: 2,
+            window: TimeDelta::from_secs(10),
+            base_cost: 20,
+        };
+        let set_config = Operation::SetReviveConfig { config };
+        assert!(execute(&mut app, admin_owner(), set_config).is_ok());
+        assert!(execute(&mut app, players[0], issue(run)).is_ok());
+        let accept = Operation::AcceptChallenge { challenge_id: 2 };
+        assert!(execute(&mut app, players[1], accept).is_ok());
+        for _ in 0..11 {
+            let up = Operation::MoveSnake {
+                direction: Direction::Up,
+            };
+            assert!(execute(&mut app, players[1], up).is_ok());
+        }
+        assert!(execute(&mut app, players[1], Operation::Revive).is_err());
+        let game = app
+            .get_game_state(&players[1])
+            .blocking_wait()
+            .expect("Game should exist");
+        assert_eq!(game.status, GameStatus::Settled);
+        let challenge = app.get_challenge(2).blocking_wait();
+        assert_eq!(challenge.status, ChallengeStatus::Completed);
+        assert_eq!(challenge.winner, None);
+
+        // Nor can a run that was revived be raced
+        assert!(execute(&mut app, players[0], Operation::StartGame).is_ok());
+        for _ in 0..11 {
+            let up = Operation::MoveSnake {
+                direction: Direction::Up,
+            };
+            assert!(execute(&mut app, players[0], up).is_ok());
+        }
+        assert!(execute(&mut app, players[0], Operation::Revive).is_ok());
+        assert!(execute(&mut app, players[0], Operation::EndGame).is_ok());
+        let revived = app
+            .get_game_state(&players[0])
+            .blocking_wait()
+            .expect("Game should exist")
+            .id;
+        assert!(execute(&mut app, players[0], issue(revived)).is_err());
     }
 
     #[test]
-    fn test_pause_and_resume() {
+    fn test_tournaments() {
+        use jeteeah::state::{Direction, GameMode, TournamentDetails};
         use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
 
         let initial_value = 0u64;
         let mut app = create_and_instantiate_app(initial_value);
+        app.execute_operation(Operation::CreateTournament {
+            details: TournamentDetails {
+                name: "Weekend Cup".to_string(),
+                starts_at: Timestamp::from(1_000_000),
+                ends_at: Timestamp::from(10_000_000),
+                mode: GameMode::Classic,
+                map_id: 0,
+                entry_fee: 20,
+                max_entrants: 4,
+                prizes: vec![50, 20],
+            },
+        })
+        .blocking_wait();
+
+        let players =
+            [1u8, 2, 3, 4, 5].map(|byte| AccountOwner::from(CryptoHash::from([byte; 32])));
+        let food_run = || [[Direction::Right; 5], [Direction::Down; 5]].concat();
+        let register = || Operation::RegisterForTournament { tournament_id: 0 };
+        let start = || Operation::StartTournamentGame { tournament_id: 0 };
+        let finalize = || Operation::FinalizeTournament { tournament_id: 0 };
+
+        for player in players {
+            grant_points(&mut app, player, 100);
+        }
+        assert!(execute(&mut app, players[0], register()).is_ok());
+        assert!(execute(&mut app, players[0], register()).is_err());
+        assert!(execute(&mut app, players[1], register()).is_ok());
+        assert!(execute(&mut app, players[2], register()).is_ok());
+        assert!(execute(&mut app, players[3], register()).is_ok());
+        assert!(execute(&mut app, players[4], register()).is_err());
+        assert_eq!(app.get_points(&players[0]).blocking_wait(), 80);
+
+        // Tournament games only start inside the window, and only for entrants
+        assert!(execute(&mut app, players[0], start()).is_err());
+        app.runtime.set_system_time(Timestamp::from(2_000_000));
+        assert!(execute(&mut app, players[4], start()).is_err());
+
+        assert!(execute(&mut app, players[0], start()).is_ok());
+        play_run(&mut app, players[0], food_run());
+        for player in [players[1], players[3]] {
+            assert!(execute(&mut app, player, start()).is_ok());
+            play_run(&mut app, player, vec![Direction::Right]);
+        }
 
-        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
-        app.runtime.set_authenticated_signer(Some(test_owner));
+        // A game started outside the tournament doesn't count
+        assert!(execute(&mut app, players[1], Operation::StartGame).is_ok());
+        play_run(&mut app, players[1], food_run());
+
+        assert!(execute(&mut app, players[4], finalize()).is_err());
+
+        // Nor does one that ends after the window closes
+        app.runtime.set_system_time(Timestamp::from(9_000_000));
+        assert!(execute(&mut app, players[1], start()).is_ok());
+        app.runtime.set_system_time(Timestamp::from(11_000_000));
+        play_run(&mut app, players[1], food_run());
+
+        // An entrant who never finished a game isn't ranked, placements past the prize
+        // table aren't recorded, and the fees the prizes don't use are kept as rake
+        assert!(execute(&mut app, players[4], finalize()).is_ok());
+        assert!(execute(&mut app, players[4], finalize()).is_err());
+
+        let tournament = app.get_tournament(0).blocking_wait();
+        let results = tournament
+            .results
+            .iter()
+            .map(|result| {
+                (
+                    result.player,
+                    result.best_score,
+                    result.placement,
+                    result.prize,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![(players[0], 10, 1, 50), (players[1], 0, 2, 20)]
+        );
+        assert_eq!(app.get_points(&players[0]).blocking_wait(), 80 + 10 + 50);
+        assert_eq!(
+            app.get_points(&players[1]).blocking_wait(),
+            80 + 10 + 10 + 20
+        );
+        assert_eq!(app.get_points(&players[2]).blocking_wait(), 80);
+        assert_eq!(app.get_points(&players[3]).blocking_wait(), 80);
+        assert_eq!(tournament.prize_pool, 80);
+        assert_eq!(*app.state.collected_rake.get(), 10);
+    }
 
-        // Start game
-        app.execute_operation(Operation::StartGame)
-            .now_or_never()
-            .expect("Start game should not await");
+    #[test]
+    fn test_games_past_the_session_limit_are_settled() {
+        use jeteeah::state::{Direction, ReviveConfig};
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash, TimeDelta};
 
-        // Pause game
-        app.execute_operation(Operation::PauseGame)
-            .now_or_never()
-            .expect("Pause game should not await");
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+        app.execute_operation(Operation::SetMaxSessionDuration {
+            duration: Some(TimeDelta::from_secs(60)),
+        })
+        .blocking_wait();
+        app.execute_operation(Operation::SetReviveConfig {
+            config: ReviveConfig {
+                max_revives: 1,
+                window: TimeDelta::from_secs(10),
+                base_cost: 20,
+            },
+        })
+        .blocking_wait();
 
         let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
-        let game_state = app.state.games.get(&player).blocking_wait()
-            .expect("Failed to read").expect("Game should exist");
+        app.runtime.set_authenticated_signer(Some(player));
+        app.execute_operation(Operation::StartGame).blocking_wait();
 
-        assert!(game_state.is_paused, "Game should be paused");
-        assert!(game_state.is_active, "Game should still be active");
+        app.runtime.set_system_time(Timestamp::from(30_000_000));
+        app.execute_operation(Operation::MoveSnake {
+            direction: Direction::Right,
+        })
+        .blocking_wait();
 
-        // Resume game
-        app.execute_operation(Operation::ResumeGame)
-            .now_or_never()
-            .expect("Resume game should not await");
+        // Past the limit the move is refused and the game is settled instead
+        app.runtime.set_system_time(Timestamp::from(61_000_000));
+        app.execute_operation(Operation::MoveSnake {
+            direction: Direction::Right,
+        })
+        .blocking_wait();
 
-        let game_state = app.state.games.get(&player).blocking_wait()
-            .expect("Failed to read").expect("Game should exist");
+        let game_state = app
+            .get_game_state(&player)
+            .blocking_wait()
+            .expect("Game should exist");
+        assert_eq!(game_state.status, GameStatus::Settled);
+        assert_eq!(game_state.end_cause, Some(EndCause::TimeLimit));
+        assert_eq!(game_state.steps, 1);
+        assert_eq!(game_state.last_move_at, Some(Timestamp::from(30_000_000)));
+        assert_eq!(game_state.ended_at, Some(Timestamp::from(61_000_000)));
+
+        let play_time = app
+            .state
+            .play_time
+            .get(&player)
+            .blocking_wait()
+            .expect("Failed to read play time");
+        assert_eq!(play_time, Some(TimeDelta::from_secs(61)));
+
+        let current_game = |app: &mut JeteeahContract| {
+            app.get_game_state(&player)
+                .blocking_wait()
+                .expect("Game should exist")
+        };
 
-        assert!(!game_state.is_paused, "Game should not be paused");
-        assert!(game_state.is_active, "Game should be active");
+        // A paused game can't be resumed past the limit
+        app.runtime.set_system_time(Timestamp::from(62_000_000));
+        app.execute_operation(Operation::StartGame).blocking_wait();
+        app.execute_operation(Operation::PauseGame).blocking_wait();
+        app.runtime.set_system_time(Timestamp::from(123_000_000));
+        app.execute_operation(Operation::ResumeGame).blocking_wait();
+        let game_state = current_game(&mut app);
+        assert_eq!(game_state.status, GameStatus::Settled);
+        assert_eq!(game_state.end_cause, Some(EndCause::TimeLimit));
+
+        // Nor can a crash be revived past it, and the revive isn't charged for
+        grant_points(&mut app, player, 100);
+        app.runtime.set_system_time(Timestamp::from(130_000_000));
+        app.execute_operation(Operation::StartGame).blocking_wait();
+        app.runtime.set_system_time(Timestamp::from(185_000_000));
+        for _ in 0..11 {
+            app.execute_operation(Operation::MoveSnake {
+                direction: Direction::Up,
+            })
+            .blocking_wait();
+        }
+        app.runtime.set_system_time(Timestamp::from(191_000_000));
+        app.execute_operation(Operation::Revive).blocking_wait();
+        let game_state = current_game(&mut app);
+        assert_eq!(game_state.status, GameStatus::Settled);
+        assert_eq!(game_state.end_cause, Some(EndCause::WallCollision));
+        assert_eq!(game_state.revive_count, 0);
+        assert_eq!(app.get_points(&player).blocking_wait(), 100);
+
+        // The sweep ends games that ran past the limit before they go stale
+        app.runtime.set_system_time(Timestamp::from(200_000_000));
+        app.execute_operation(Operation::StartGame).blocking_wait();
+        app.runtime.set_system_time(Timestamp::from(261_000_000));
+        app.execute_operation(Operation::ExpireStaleGames { limit: 10 })
+            .blocking_wait();
+        let game_state = current_game(&mut app);
+        assert_eq!(game_state.status, GameStatus::Settled);
+        assert_eq!(game_state.end_cause, Some(EndCause::TimeLimit));
     }
 
     #[test]
-    fn test_end_game_awards_points() {
-        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+    fn test_expire_stale_games() {
+        use jeteeah::state::ExpiryConfig;
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash, TimeDelta};
 
         let initial_value = 0u64;
         let mut app = create_and_instantiate_app(initial_value);
+        app.execute_operation(Operation::SetExpiryConfig {
+            config: ExpiryConfig {
+                inactivity_timeout: TimeDelta::from_secs(100),
+                max_pause: TimeDelta::from_secs(10),
+            },
+        })
+        .blocking_wait();
 
-        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
-        app.runtime.set_authenticated_signer(Some(test_owner));
+        let players = [1u8, 2, 3, 4].map(|byte| AccountOwner::from(CryptoHash::from([byte; 32])));
+        for player in players {
+            app.runtime.set_authenticated_signer(Some(player));
+            app.execute_operation(Operation::StartGame).blocking_wait();
+        }
 
-        // Start game
-        app.execute_operation(Operation::StartGame)
+        // The second player keeps playing, and the third pauses
+        app.runtime.set_system_time(Timestamp::from(50_000_000));
+        app.runtime.set_authenticated_signer(Some(players[1]));
+        app.execute_operation(Operation::PauseGame).blocking_wait();
+        app.execute_operation(Operation::ResumeGame).blocking_wait();
+        app.runtime.set_authenticated_signer(Some(players[2]));
+        app.execute_operation(Operation::PauseGame).blocking_wait();
+
+        // Each sweep is bounded, and the second picks up where the first stopped, with
+        // the game still in play sent to the back of the queue
+        app.runtime.set_system_time(Timestamp::from(120_000_000));
+        app.execute_operation(Operation::ExpireStaleGames { limit: 2 })
             .blocking_wait();
-
-        // End game
-        app.execute_operation(Operation::EndGame)
+        assert_eq!(app.state.active_players.count().blocking_wait().unwrap(), 3);
+        app.execute_operation(Operation::ExpireStaleGames { limit: 2 })
             .blocking_wait();
 
-        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
-        let game_state = app.state.games.get(&player).blocking_wait()
-            .expect("Failed to read").expect("Game should exist");
-
-        assert!(!game_state.is_active, "Game should be inactive");
-
-        // Check points were awarded (score was 0, so points should be 0)
-        let points = app.state.points.get(&player).blocking_wait()
-            .expect("Failed to read points").unwrap_or(0);
-        assert_eq!(points, game_state.score, "Points should equal score");
+        let status = |app: &mut JeteeahContract, player| {
+            app.get_game_state(&player)
+                .blocking_wait()
+                .expect("Game should exist")
+                .status
+        };
+        assert_eq!(status(&mut app, players[0]), GameStatus::Settled);
+        assert_eq!(status(&mut app, players[1]), GameStatus::Active);
+        assert_eq!(status(&mut app, players[2]), GameStatus::Settled);
+        assert_eq!(status(&mut app, players[3]), GameStatus::Settled);
+        let game_state = app
+            .get_game_state(&players[0])
+            .blocking_wait()
+            .expect("Game should exist");
+        assert_eq!(game_state.end_cause, Some(EndCause::Expired));
+        assert_eq!(app.state.active_players.count().blocking_wait().unwrap(), 1);
     }
 
     #[test]
-    fn test_reset_game() {
+    fn test_expiry_sweeps_resume_past_many_entries() {
         use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
 
         let initial_value = 0u64;
         let mut app = create_and_instantiate_app(initial_value);
+        let players = (1u8..=12)
+            .map(|byte| AccountOwner::from(CryptoHash::from([byte; 32])))
+            .collect::<Vec<_>>();
+        for player in &players {
+            assert!(execute(&mut app, *player, Operation::StartGame).is_ok());
+        }
+        // The first five games are settled before any sweep, leaving their entries behind
+        for player in &players[..5] {
+            assert!(execute(&mut app, *player, Operation::EndGame).is_ok());
+        }
 
-        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
-        app.runtime.set_authenticated_signer(Some(test_owner));
-
-        // Start game
-        app.execute_operation(Operation::StartGame)
-            .now_or_never()
-            .expect("Start game should not await");
+        // Each sweep only takes its own share of the queue, however far along it is
+        app.runtime.set_system_time(Timestamp::from(4_000_000_000));
+        let sweep = || Operation::ExpireStaleGames { limit: 4 };
+        let active = |app: &JeteeahContract| app.state.active_players.count().blocking_wait();
+        for (queued, active_players) in [(8, 7), (4, 4), (0, 0)] {
+            assert!(execute(&mut app, players[0], sweep()).is_ok());
+            assert_eq!(app.state.expiry_queue.count(), queued);
+            assert_eq!(active(&app).unwrap(), active_players);
+        }
+        for player in &players[5..] {
+            let game = app
+                .get_game_state(player)
+                .blocking_wait()
+                .expect("Game should exist");
+            assert_eq!(game.end_cause, Some(EndCause::Expired));
+        }
+    }
 
-        // Reset game
-        app.execute_operation(Operation::ResetGame)
-            .now_or_never()
-            .expect("Reset game should not await");
+    #[test]
+    fn test_room_lobby() {
+        use jeteeah::state::RoomStatus;
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
 
-        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
-        let game_state = app.state.games.get(&player).blocking_wait()
-            .expect("Failed to read").expect("Game should exist");
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+        let players = [1u8, 2, 3, 4].map(|byte| AccountOwner::from(CryptoHash::from([byte; 32])));
+
+        assert!(execute(
+            &mut app,
+            players[0],
+            Operation::CreateRoom {
+                capacity: 3,
+                commit_reveal: false,
+                stake: 0,
+            }
+        )
+        .is_ok());
+        let code = app.current_room(&players[0]).blocking_wait().code;
+        let join = || Operation::JoinRoom { code: code.clone() };
+        assert!(execute(&mut app, players[1], join()).is_ok());
+
+        // The host leaving hands the room to the next member
+        assert!(execute(&mut app, players[0], Operation::LeaveRoom).is_ok());
+        assert!(execute(&mut app, players[2], join()).is_ok());
+        assert!(execute(&mut app, players[3], join()).is_ok());
+        let room = app.current_room(&players[1]).blocking_wait();
+        assert_eq!(room.host, players[1]);
+        assert_eq!(room.members.len(), 3);
+
+        // Everyone must be ready, and only the host can start
+        assert!(execute(&mut app, players[1], Operation::StartRoom).is_err());
+        for player in &players[1..] {
+            assert!(execute(&mut app, *player, Operation::ToggleReady).is_ok());
+        }
+        assert!(execute(&mut app, players[2], Operation::StartRoom).is_err());
+        assert!(execute(&mut app, players[1], Operation::StartRoom).is_ok());
 
-        // Should be a fresh game
-        assert_eq!(game_state.score, 0);
-        assert_eq!(game_state.snake_body.len(), 3);
-        assert!(game_state.is_active);
-        assert!(!game_state.is_paused);
+        let room = app.current_room(&players[1]).blocking_wait();
+        assert_eq!(room.status, RoomStatus::Playing);
+        assert!(execute(&mut app, players[0], join()).is_err());
     }
 
     #[test]
-    fn test_add_and_redeem_points() {
+    fn test_room_game_ends_with_placements() {
+        use jeteeah::state::{Direction, RoomStatus};
         use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
 
         let initial_value = 0u64;
         let mut app = create_and_instantiate_app(initial_value);
+        let players = [1u8, 2].map(|byte| AccountOwner::from(CryptoHash::from([byte; 32])));
+
+        app.runtime.set_authenticated_signer(Some(players[0]));
+        app.execute_operation(Operation::CreateRoom {
+            capacity: 2,
+            commit_reveal: false,
+            stake: 0,
+        })
+        .blocking_wait();
+        let code = app.current_room(&players[0]).blocking_wait().code;
+        app.runtime.set_authenticated_signer(Some(players[1]));
+        app.execute_operation(Operation::JoinRoom { code })
+            .blocking_wait();
+        for player in players {
+            app.runtime.set_authenticated_signer(Some(player));
+            app.execute_operation(Operation::ToggleReady)
+                .blocking_wait();
+        }
+        app.runtime.set_authenticated_signer(Some(players[0]));
+        app.execute_operation(Operation::StartRoom).blocking_wait();
+
+        // The second snake starts on row 13 and turns down into the wall on the 7th tick
+        for tick in 1..=7 {
+            for (player, direction) in players.into_iter().zip([Direction::Right, Direction::Down])
+            {
+                app.runtime.set_authenticated_signer(Some(player));
+                app.execute_operation(Operation::SubmitMove { tick, direction })
+                    .blocking_wait();
+            }
+        }
 
-        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
-        app.runtime.set_authenticated_signer(Some(test_owner));
+        let room = app.get_room(0).blocking_wait();
+        assert_eq!(room.status, RoomStatus::Finished);
+        let standings = room
+            .results
+            .iter()
+            .map(|result| (result.player, result.placement, result.eliminated_at))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            standings,
+            vec![(players[0], 1, None), (players[1], 2, Some(7))]
+        );
+        let board = app.get_board(0).blocking_wait();
+        assert_eq!(board.snakes[1].end_cause, Some(EndCause::WallCollision));
 
-        // Add points
-        app.execute_operation(Operation::AddPoints { amount: 100 })
-            .blocking_wait();
+        // Spectators saw the starting board and every tick, and the room is no longer live
+        let frames = app
+            .state
+            .room_frames
+            .get(&0)
+            .blocking_wait()
+            .expect("Failed to read room frames")
+            .expect("The room should have frames");
+        assert_eq!(
+            frames.iter().map(|frame| frame.tick).collect::<Vec<_>>(),
+            (0..=7).collect::<Vec<_>>()
+        );
+        assert!(!app
+            .state
+            .playing_rooms
+            .contains(&0)
+            .blocking_wait()
+            .unwrap());
+
+        // Both players are free to join other rooms again
+        app.execute_operation(Operation::CreateRoom {
+            capacity: 2,
+            commit_reveal: false,
+            stake: 0,
+        })
+        .blocking_wait();
+    }
 
-        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
-        let points = app.state.points.get(&player).blocking_wait()
-            .expect("Failed to read points").unwrap_or(0);
-        assert_eq!(points, 100);
+    #[test]
+    fn test_wagered_rooms_pay_out_or_refund_stakes() {
+        use jeteeah::{
+            state::{Direction, Escrow, EscrowStatus, RoomResult, RoomStatus},
+            Message, RoomAction,
+        };
+        use linera_sdk::linera_base_types::{AccountOwner, ChainId, CryptoHash};
 
-        // Redeem points
-        app.execute_operation(Operation::RedeemPoints { amount: 50 })
-            .blocking_wait();
+        let initial_value = 0u64;
+        let mut app = create_and_instantiate_app(initial_value);
+        let players = [1u8, 2].map(|byte| AccountOwner::from(CryptoHash::from([byte; 32])));
+        for player in players {
+            grant_points(&mut app, player, 100);
+        }
+        let start_wagered_room = |app: &mut JeteeahContract, stake| {
+            let create = Operation::CreateRoom {
+                capacity: 2,
+                commit_reveal: false,
+                stake,
+            };
+            assert!(execute(app, players[0], create).is_ok());
+            let code = app.current_room(&players[0]).blocking_wait().code;
+            assert!(execute(app, players[1], Operation::JoinRoom { code }).is_ok());
+            for player in players {
+                assert!(execute(app, player, Operation::ToggleReady).is_ok());
+            }
+            assert!(execute(app, players[0], Operation::StartRoom).is_ok());
+            app.current_room(&players[0]).blocking_wait().id
+        };
 
-        let points = app.state.points.get(&player).blocking_wait()
-            .expect("Failed to read points").unwrap_or(0);
-        assert_eq!(points, 50);
+        // Nobody can wager more than they have
+        let too_much = Operation::CreateRoom {
+            capacity: 2,
+            commit_reveal: false,
+            stake: 101,
+        };
+        assert!(execute(&mut app, players[0], too_much).is_err());
 
-        // Try to redeem more than available (should fail silently)
-        app.execute_operation(Operation::RedeemPoints { amount: 100 })
-            .blocking_wait();
+        // Stakes are locked when the room starts
+        let room_id = start_wagered_room(&mut app, 50);
+        for player in players {
+            assert_eq!(app.get_points(&player).blocking_wait(), 50);
+        }
 
-        let points = app.state.points.get(&player).blocking_wait()
-            .expect("Failed to read points").unwrap_or(0);
-        assert_eq!(points, 50, "Points should not change if insufficient");
+        // The winner takes the pot, less the default 5% rake
+        for tick in 1..=7 {
+            for (player, direction) in players.into_iter().zip([Direction::Right, Direction::Down])
+            {
+                let submit = Operation::SubmitMove { tick, direction };
+                assert!(execute(&mut app, player, submit).is_ok());
+            }
+        }
+        let room = app.get_room(room_id).blocking_wait();
+        assert_eq!(room.status, RoomStatus::Finished);
+        assert_eq!(room.results[0].payout, 95);
+        assert_eq!(app.get_points(&players[0]).blocking_wait(), 145);
+        assert_eq!(app.get_points(&players[1]).blocking_wait(), 50);
+        assert_eq!(*app.state.collected_rake.get(), 5);
+        let escrow = app.held_escrow(room_id).blocking_wait();
+        assert!(escrow.is_none());
+
+        // A draw splits the pot, with the odd point going to the rake
+        let mut room = app.get_room(room_id).blocking_wait();
+        room.id = 99;
+        room.results = players
+            .map(|player| RoomResult {
+                player,
+                score: 0,
+                placement: 1,
+                eliminated_at: Some(3),
+                payout: 0,
+            })
+            .to_vec();
+        app.save_escrow(Escrow {
+            room_id: 99,
+            stake: 50,
+            players: players.to_vec(),
+            pot: 101,
+            rake_bps: 0,
+            rake: 0,
+            status: EscrowStatus::Held,
+        });
+        app.pay_out_pot(&mut room).blocking_wait();
+        assert!(room.results.iter().all(|result| result.payout == 50));
+        assert_eq!(app.get_points(&players[0]).blocking_wait(), 195);
+        assert_eq!(app.get_points(&players[1]).blocking_wait(), 100);
+        assert_eq!(*app.state.collected_rake.get(), 6);
+
+        // Leaving before the first tick calls the room off and refunds everyone
+        let room_id = start_wagered_room(&mut app, 20);
+        assert_eq!(app.get_points(&players[1]).blocking_wait(), 80);
+        assert!(execute(&mut app, players[1], Operation::LeaveRoom).is_ok());
+        assert_eq!(
+            app.get_room(room_id).blocking_wait().status,
+            RoomStatus::Cancelled
+        );
+        assert_eq!(app.get_points(&players[0]).blocking_wait(), 195);
+        assert_eq!(app.get_points(&players[1]).blocking_wait(), 100);
+
+        // So does a player who never makes their first move
+        let room_id = start_wagered_room(&mut app, 20);
+        let submit = Operation::SubmitMove {
+            tick: 1,
+            direction: Direction::Right,
+        };
+        assert!(execute(&mut app, players[0], submit).is_ok());
+        app.runtime.set_system_time(Timestamp::from(3_000_000));
+        assert!(execute(&mut app, players[0], Operation::ResolveTick { room_id }).is_ok());
+        assert_eq!(
+            app.get_room(room_id).blocking_wait().status,
+            RoomStatus::Cancelled
+        );
+        assert_eq!(app.get_points(&players[0]).blocking_wait(), 195);
+        assert_eq!(app.get_points(&players[1]).blocking_wait(), 100);
+        let escrow = app
+            .state
+            .room_escrows
+            .get(&room_id)
+            .blocking_wait()
+            .expect("Failed to read escrow")
+            .expect("The room should have an escrow");
+        assert_eq!(escrow.status, EscrowStatus::Refunded);
+
+        // Players on other chains can't join wagered rooms
+        let create = Operation::CreateRoom {
+            capacity: 2,
+            commit_reveal: false,
+            stake: 20,
+        };
+        assert!(execute(&mut app, players[0], create).is_ok());
+        let code = app.current_room(&players[0]).blocking_wait().code;
+        app.runtime.set_authenticated_signer(Some(players[1]));
+        app.runtime
+            .set_message_origin_chain_id(Some(ChainId(CryptoHash::from([8u8; 32]))));
+        app.runtime.set_message_is_bouncing(Some(false));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            app.execute_message(Message::RoomAction {
+                action: RoomAction::Join { code },
+            })
+            .blocking_wait()
+        }));
+        assert!(result.is_err());
+
+        // Only the admin can withdraw the rake, and no more than was collected
+        let withdraw = |amount| Operation::WithdrawRake { amount };
+        assert!(execute(&mut app, players[0], withdraw(6)).is_err());
+        assert!(execute(&mut app, admin_owner(), withdraw(7)).is_err());
+        assert!(execute(&mut app, admin_owner(), withdraw(6)).is_ok());
+        assert_eq!(*app.state.collected_rake.get(), 0);
+        assert_eq!(app.get_points(&admin_owner()).blocking_wait(), 6);
+
+        // A member who can no longer cover the stake is unreadied rather than blocking
+        // the start, and can't ready up again
+        let code = app.current_room(&players[0]).blocking_wait().code;
+        assert!(execute(&mut app, players[1], Operation::JoinRoom { code }).is_ok());
+        for player in players {
+            assert!(execute(&mut app, player, Operation::ToggleReady).is_ok());
+        }
+        let spend = Operation::RedeemPoints { amount: 90 };
+        assert!(execute(&mut app, players[1], spend).is_ok());
+        assert!(execute(&mut app, players[0], Operation::StartRoom).is_ok());
+        let room = app.current_room(&players[0]).blocking_wait();
+        assert_eq!(room.status, RoomStatus::Waiting);
+        assert!(!room.members[1].ready);
+        assert_eq!(app.get_points(&players[0]).blocking_wait(), 195);
+        assert!(execute(&mut app, players[1], Operation::ToggleReady).is_err());
     }
 
     #[test]
-    fn test_set_game_parameters() {
+    fn test_room_ticks_resolve_at_the_deadline() {
+        use jeteeah::state::{Direction, Position, RoomConfig};
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash, TimeDelta};
+
         let initial_value = 0u64;
         let mut app = create_and_instantiate_app(initial_value);
+        app.execute_operation(Operation::SetRoomConfig {
+            config: RoomConfig {
+                tick_duration: TimeDelta::from_secs(2),
+                ..RoomConfig::default()
+            },
+        })
+        .blocking_wait();
+
+        let players = [1u8, 2, 3].map(|byte| AccountOwner::from(CryptoHash::from([byte; 32])));
+
+        assert!(execute(
+            &mut app,
+            players[0],
+            Operation::CreateRoom {
+                capacity: 2,
+                commit_reveal: false,
+                stake: 0,
+            }
+        )
+        .is_ok());
+        let code = app.current_room(&players[0]).blocking_wait().code;
+        assert!(execute(&mut app, players[1], Operation::JoinRoom { code }).is_ok());
+        for player in &players[..2] {
+            assert!(execute(&mut app, *player, Operation::ToggleReady).is_ok());
+        }
+        assert!(execute(&mut app, players[0], Operation::StartRoom).is_ok());
 
-        // Set new dimensions
-        app.execute_operation(Operation::SetGameParameters { width: 30, height: 25 })
-            .now_or_never()
-            .expect("Set game parameters should not await");
+        let submit = |tick| Operation::SubmitMove {
+            tick,
+            direction: Direction::Down,
+        };
+        assert!(execute(&mut app, players[0], submit(2)).is_err());
+        assert!(execute(&mut app, players[0], submit(1)).is_ok());
+        assert!(execute(&mut app, players[0], submit(1)).is_err());
+        assert_eq!(
+            app.get_board(0).blocking_wait().pending_players(),
+            vec![players[1]]
+        );
 
-        assert_eq!(*app.state.game_width.get(), 30);
-        assert_eq!(*app.state.game_height.get(), 25);
+        // Anyone can resolve the tick, but only after the deadline
+        let resolve = || Operation::ResolveTick { room_id: 0 };
+        assert!(execute(&mut app, players[2], resolve()).is_err());
+        app.runtime.set_system_time(Timestamp::from(3_000_000));
+        assert!(execute(&mut app, players[2], resolve()).is_ok());
+
+        // The silent player carried on straight, to the left
+        let board = app.get_board(0).blocking_wait();
+        assert_eq!(board.tick, 1);
+        assert_eq!(board.snakes[0].body[0], Position { x: 2, y: 7 });
+        assert_eq!(board.snakes[1].body[0], Position { x: 16, y: 13 });
+        assert_eq!(board.tick_deadline, Timestamp::from(5_000_000));
     }
 
     #[test]
-    fn test_update_food_spawn_rate() {
+    fn test_commit_reveal_moves() {
+        use jeteeah::state::{Direction, MoveCommitment, Position, RoomConfig, TickPhase};
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash, TimeDelta};
+
         let initial_value = 0u64;
         let mut app = create_and_instantiate_app(initial_value);
+        app.execute_operation(Operation::SetRoomConfig {
+            config: RoomConfig {
+                tick_duration: TimeDelta::from_secs(2),
+                reveal_duration: TimeDelta::from_secs(1),
+                missed_reveal_penalty: 10,
+                ..RoomConfig::default()
+            },
+        })
+        .blocking_wait();
 
-        // Update food spawn rate
-        app.execute_operation(Operation::UpdateFoodSpawnRate { rate: 5 })
-            .now_or_never()
-            .expect("Update food spawn rate should not await");
+        let players = [1u8, 2, 3].map(|byte| AccountOwner::from(CryptoHash::from([byte; 32])));
 
-        assert_eq!(*app.state.food_spawn_rate.get(), 5);
+        let create = Operation::CreateRoom {
+            capacity: 2,
+            commit_reveal: true,
+            stake: 0,
+        };
+        assert!(execute(&mut app, players[0], create).is_ok());
+        let code = app.current_room(&players[0]).blocking_wait().code;
+        assert!(execute(&mut app, players[1], Operation::JoinRoom { code }).is_ok());
+        for player in &players[..2] {
+            assert!(execute(&mut app, *player, Operation::ToggleReady).is_ok());
+        }
+        assert!(execute(&mut app, players[0], Operation::StartRoom).is_ok());
+
+        let submit = Operation::SubmitMove {
+            tick: 1,
+            direction: Direction::Down,
+        };
+        assert!(execute(&mut app, players[0], submit).is_err());
+
+        let commitment = |player, direction| {
+            MoveCommitment {
+                player,
+                tick: 1,
+                direction,
+                salt: "pepper".to_string(),
+            }
+            .hash()
+        };
+        let reveal = |direction, salt: &str| Operation::RevealMove {
+            tick: 1,
+            direction,
+            salt: salt.to_string(),
+        };
+        for player in &players[..2] {
+            let commit = Operation::CommitMove {
+                tick: 1,
+                commitment: commitment(*player, Direction::Down),
+            };
+            assert!(execute(&mut app, *player, commit).is_ok());
+        }
+        let board = app.get_board(0).blocking_wait();
+        assert_eq!(board.phase, TickPhase::Reveal);
+        assert_eq!(board.tick_deadline, Timestamp::from(1_000_000));
+
+        // Reveals must match the commitment exactly
+        assert!(execute(&mut app, players[0], reveal(Direction::Up, "pepper")).is_err());
+        assert!(execute(&mut app, players[0], reveal(Direction::Down, "salt")).is_err());
+        assert!(execute(&mut app, players[0], reveal(Direction::Down, "pepper")).is_ok());
+
+        // The other player never reveals, so carries on straight and is penalised
+        let resolve = || Operation::ResolveTick { room_id: 0 };
+        assert!(execute(&mut app, players[2], resolve()).is_err());
+        app.runtime.set_system_time(Timestamp::from(2_000_000));
+        assert!(execute(&mut app, players[2], resolve()).is_ok());
+
+        let board = app.get_board(0).blocking_wait();
+        assert_eq!(board.tick, 1);
+        assert_eq!(board.phase, TickPhase::Submit);
+        assert_eq!(board.snakes[0].body[0], Position { x: 2, y: 7 });
+        assert_eq!(board.snakes[1].body[0], Position { x: 16, y: 13 });
+        assert_eq!(board.snakes[0].missed_reveals, 0);
+        assert_eq!(board.snakes[1].missed_reveals, 1);
+        assert!(board.snakes.iter().all(|snake| snake.commitment.is_none()));
+
+        // Once the commit deadline passes, the reveals open without the silent player
+        let commit = Operation::CommitMove {
+            tick: 2,
+            commitment: commitment(players[0], Direction::Down),
+        };
+        assert!(execute(&mut app, players[0], commit).is_ok());
+        app.runtime.set_system_time(Timestamp::from(5_000_000));
+        assert!(execute(&mut app, players[2], resolve()).is_ok());
+        let board = app.get_board(0).blocking_wait();
+        assert_eq!(board.phase, TickPhase::Reveal);
+        assert_eq!(board.pending_players(), vec![players[0]]);
     }
 
     #[test]
-    fn test_collision_detection_wall() {
-        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
-        use jeteeah::state::Direction;
+    fn test_rooms_across_chains() {
+        use jeteeah::{
+            state::{Direction, RoomStatus},
+            Message, RoomAction,
+        };
+        use linera_sdk::linera_base_types::{AccountOwner, ChainId, CryptoHash};
+
+        let players = [1u8, 2].map(|byte| AccountOwner::from(CryptoHash::from([byte; 32])));
+        let host_chain = ChainId(CryptoHash::from([7u8; 32]));
+        let player_chain = ChainId(CryptoHash::from([8u8; 32]));
+
+        // On the player's chain, joining sends a signed, tracked action to the host
+        let mut guest = create_and_instantiate_app(0);
+        guest.runtime.set_chain_id(player_chain);
+        guest.runtime.set_authenticated_signer(Some(players[1]));
+        guest
+            .execute_operation(Operation::JoinRemoteRoom {
+                host_chain,
+                code: "ABCDEF".to_string(),
+            })
+            .blocking_wait();
+        guest
+            .execute_operation(Operation::ToggleReady)
+            .blocking_wait();
+        {
+            let messages = guest.runtime.created_send_message_requests();
+            assert_eq!(messages.len(), 2);
+            assert!(messages
+                .iter()
+                .all(|message| message.destination == host_chain
+                    && message.authenticated
+                    && message.is_tracked));
+            assert!(matches!(
+                &messages[0].message,
+                Message::RoomAction {
+                    action: RoomAction::Join { code }
+                } if code == "ABCDEF"
+            ));
+            assert!(matches!(
+                messages[1].message,
+                Message::RoomAction {
+                    action: RoomAction::ToggleReady
+                }
+            ));
+        }
 
-        let initial_value = 0u64;
-        let mut app = create_and_instantiate_app(initial_value);
+        // On the host chain, the action is performed for its signer, who is sent the
+        // room's new state
+        let mut host = create_and_instantiate_app(0);
+        host.runtime.set_chain_id(host_chain);
+        host.runtime.set_authenticated_signer(Some(players[0]));
+        host.execute_operation(Operation::CreateRoom {
+            capacity: 2,
+            commit_reveal: false,
+            stake: 0,
+        })
+        .blocking_wait();
+        let code = host.current_room(&players[0]).blocking_wait().code;
+
+        host.runtime.set_authenticated_signer(Some(players[1]));
+        host.runtime.set_message_origin_chain_id(Some(player_chain));
+        host.runtime.set_message_is_bouncing(Some(false));
+        host.execute_message(Message::RoomAction {
+            action: RoomAction::Join { code },
+        })
+        .blocking_wait();
+        host.execute_message(Message::RoomAction {
+            action: RoomAction::ToggleReady,
+        })
+        .blocking_wait();
+
+        let room = host.current_room(&players[1]).blocking_wait();
+        assert_eq!(room.members.len(), 2);
+        assert!(room.members[1].ready);
+        let update = {
+            let messages = host.runtime.created_send_message_requests();
+            assert_eq!(messages.len(), 2);
+            assert_eq!(messages[1].destination, player_chain);
+            messages[1].message.clone()
+        };
 
-        let test_owner = AccountOwner::from(CryptoHash::from([1u8; 32]));
-        app.runtime.set_authenticated_signer(Some(test_owner));
+        // The player's chain only takes updates from the chain they joined on
+        let remote_room = |app: &mut JeteeahContract| {
+            app.state
+                .remote_rooms
+                .get(&players[1])
+                .blocking_wait()
+                .expect("Failed to read remote room")
+        };
+        guest
+            .runtime
+            .set_message_origin_chain_id(Some(ChainId(CryptoHash::from([9u8; 32]))));
+        guest.execute_message(update.clone()).blocking_wait();
+        assert!(remote_room(&mut guest).is_none());
+
+        guest.runtime.set_message_origin_chain_id(Some(host_chain));
+        guest.execute_message(update).blocking_wait();
+        let remote = remote_room(&mut guest).expect("Room update should be recorded");
+        assert_eq!(remote.host_chain, host_chain);
+        assert!(remote.room.members[1].ready);
+
+        // Leaving only takes effect once the host's update confirms it
+        let in_remote_room = |app: &mut JeteeahContract| {
+            app.state
+                .remote_room_hosts
+                .contains_key(&players[1])
+                .blocking_wait()
+                .unwrap()
+        };
+        let from_guest = |host: &mut JeteeahContract, action| {
+            host.runtime.set_authenticated_signer(Some(players[1]));
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                host.execute_message(Message::RoomAction { action })
+                    .blocking_wait()
+            }))
+        };
+        let latest_update = |host: &mut JeteeahContract| {
+            host.runtime
+                .created_send_message_requests()
+                .iter()
+                .rev()
+                .find(|message| message.destination == player_chain)
+                .expect("The player's chain should be sent an update")
+                .message
+                .clone()
+        };
+        guest
+            .execute_operation(Operation::LeaveRoom)
+            .blocking_wait();
+        assert!(in_remote_room(&mut guest));
+        assert!(from_guest(&mut host, RoomAction::Leave).is_ok());
+        assert_eq!(
+            host.current_room(&players[0]).blocking_wait().members.len(),
+            1
+        );
+        let update = latest_update(&mut host);
+        guest.execute_message(update).blocking_wait();
+        assert!(!in_remote_room(&mut guest));
+        assert!(remote_room(&mut guest).is_none());
+
+        // Once the game is under way the host rejects a leave, and the player keeps
+        // their seat when it bounces back
+        let code = host.current_room(&players[0]).blocking_wait().code;
+        guest
+            .execute_operation(Operation::JoinRemoteRoom {
+                host_chain,
+                code: code.clone(),
+            })
+            .blocking_wait();
+        assert!(from_guest(&mut host, RoomAction::Join { code }).is_ok());
+        assert!(from_guest(&mut host, RoomAction::ToggleReady).is_ok());
+        host.runtime.set_authenticated_signer(Some(players[0]));
+        host.execute_operation(Operation::ToggleReady)
+            .blocking_wait();
+        host.execute_operation(Operation::StartRoom).blocking_wait();
+        host.execute_operation(Operation::SubmitMove {
+            tick: 1,
+            direction: Direction::Right,
+        })
+        .blocking_wait();
+        let down = RoomAction::SubmitMove {
+            tick: 1,
+            direction: Direction::Down,
+        };
+        assert!(from_guest(&mut host, down).is_ok());
+        assert_eq!(host.get_board(0).blocking_wait().tick, 1);
 
-        // Start game
-        app.execute_operation(Operation::StartGame)
+        guest
+            .execute_operation(Operation::LeaveRoom)
+            .blocking_wait();
+        assert!(from_guest(&mut host, RoomAction::Leave).is_err());
+        guest.runtime.set_message_is_bouncing(Some(true));
+        guest
+            .execute_message(Message::RoomAction {
+                action: RoomAction::Leave,
+            })
+            .blocking_wait();
+        guest.runtime.set_message_is_bouncing(Some(false));
+        assert!(in_remote_room(&mut guest));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            guest
+                .execute_operation(Operation::JoinRemoteRoom {
+                    host_chain,
+                    code: "GHIJKL".to_string(),
+                })
+                .blocking_wait()
+        }));
+        assert!(result.is_err());
+
+        // A finished room frees the player, so room operations run locally again
+        let update = latest_update(&mut host);
+        guest.execute_message(update).blocking_wait();
+        let remote = remote_room(&mut guest).expect("Room update should be recorded");
+        let mut room = remote.room;
+        room.status = RoomStatus::Finished;
+        guest
+            .execute_message(Message::RoomUpdate {
+                room,
+                board: remote.board,
+                departed: Vec::new(),
+            })
             .blocking_wait();
+        assert!(!guest
+            .state
+            .remote_room_hosts
+            .contains_key(&players[1])
+            .blocking_wait()
+            .unwrap());
+    }
 
-        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
-        
-        // Snake starts at x=10, y=10. Move Up 11 times to reach y=-1 (collision with top wall)
-        for _ in 0..11 {
-            app.execute_operation(Operation::MoveSnake { direction: Direction::Up })
-                .blocking_wait();
-        }
+    #[test]
+    fn test_shared_board_collisions() {
+        use jeteeah::{
+            rules,
+            state::{Direction, Position},
+        };
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
 
-        let game_state = app.state.games.get(&player).blocking_wait()
-            .expect("Failed to read").expect("Game should exist");
-        
-        // Check that collision was detected
-        assert!(!game_state.is_active, "Game should be inactive after wall collision. Final y position: {}", game_state.snake_body[0].y);
+        let players = [1u8, 2, 3].map(|byte| AccountOwner::from(CryptoHash::from([byte; 32])));
+        let row = |xs: [i32; 3], y| xs.map(|x| Position { x, y }).to_vec();
+        let board_with = |bodies: Vec<(Vec<Position>, Direction)>| {
+            let mut board = rules::new_board(0, 0, 20, 20, &players[..bodies.len()]);
+            board.food_position = Position { x: 0, y: 19 };
+            for (snake, (body, direction)) in board.snakes.iter_mut().zip(bodies) {
+                snake.body = body;
+                snake.direction = direction;
+            }
+            board
+        };
+
+        // Moving into the same cell knocks both snakes out, and they tie
+        let mut board = board_with(vec![
+            (row([4, 3, 2], 5), Direction::Right),
+            (row([6, 7, 8], 5), Direction::Left),
+        ]);
+        rules::step_board(&mut board, &[None, None]);
+        assert!(board
+            .snakes
+            .iter()
+            .all(|snake| snake.end_cause == Some(EndCause::HeadOn)));
+        let placements = rules::room_results(&board)
+            .iter()
+            .map(|result| result.placement)
+            .collect::<Vec<_>>();
+        assert_eq!(placements, vec![1, 1]);
+
+        // So does passing through each other
+        let mut board = board_with(vec![
+            (row([4, 3, 2], 5), Direction::Right),
+            (row([5, 6, 7], 5), Direction::Left),
+        ]);
+        rules::step_board(&mut board, &[None, None]);
+        assert!(board
+            .snakes
+            .iter()
+            .all(|snake| snake.end_cause == Some(EndCause::HeadOn)));
+
+        // Running into a body only knocks out the runner, who places last
+        let mut board = board_with(vec![
+            (row([5, 4, 3], 4), Direction::Right),
+            (row([6, 5, 4], 5), Direction::Right),
+            (row([2, 1, 0], 9), Direction::Right),
+        ]);
+        rules::step_board(&mut board, &[Some(Direction::Down), None, None]);
+        assert_eq!(board.snakes[0].end_cause, Some(EndCause::SnakeCollision));
+        assert!(board.snakes[1].alive && board.snakes[2].alive);
+        assert!(!rules::is_board_over(&board));
+        let placements = rules::room_results(&board)
+            .iter()
+            .map(|result| result.placement)
+            .collect::<Vec<_>>();
+        assert_eq!(placements, vec![3, 1, 1]);
+    }
+
+    fn admin_owner() -> linera_sdk::linera_base_types::AccountOwner {
+        use linera_sdk::linera_base_types::{AccountOwner, CryptoHash};
+
+        AccountOwner::from(CryptoHash::from([0u8; 32]))
+    }
+
+    /// Has the admin grant points to a player, keeping the current signer
+    fn grant_points(
+        app: &mut JeteeahContract,
+        player: linera_sdk::linera_base_types::AccountOwner,
+        amount: u64,
+    ) {
+        let signer = app.runtime.authenticated_signer();
+        app.runtime.set_authenticated_signer(Some(admin_owner()));
+        app.execute_operation(Operation::AddPoints {
+            target: player,
+            amount,
+        })
+        .blocking_wait();
+        app.runtime.set_authenticated_signer(signer);
+    }
+
+    /// Runs an operation signed by the player, catching the panic if it's rejected
+    fn execute(
+        app: &mut JeteeahContract,
+        player: linera_sdk::linera_base_types::AccountOwner,
+        operation: Operation,
+    ) -> std::thread::Result<()> {
+        app.runtime.set_authenticated_signer(Some(player));
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            app.execute_operation(operation).blocking_wait()
+        }))
+    }
+
+    /// Submits the inputs as a run of the player's current game, returning its id
+    fn play_run(
+        app: &mut JeteeahContract,
+        player: linera_sdk::linera_base_types::AccountOwner,
+        inputs: Vec<jeteeah::state::Direction>,
+    ) -> u64 {
+        let game_id = app
+            .get_game_state(&player)
+            .blocking_wait()
+            .expect("Game should exist")
+            .id;
+        assert!(execute(app, player, Operation::SubmitRun { game_id, inputs }).is_ok());
+        game_id
     }
 
     fn create_and_instantiate_app(initial_value: u64) -> JeteeahContract {
-        let runtime = ContractRuntime::new().with_application_parameters(());
+        let runtime = ContractRuntime::new()
+            .with_application_parameters(())
+            .with_authenticated_signer(admin_owner())
+            .with_system_time(Timestamp::from(0));
         let mut contract = JeteeahContract {
             state: JeteeahState::load(runtime.root_view_storage_context())
                 .blocking_wait()
                 .expect("Failed to read from mock key value store"),
             runtime,
+            updated_rooms: BTreeMap::new(),
         };
 
         contract
@@ -1,10 +1,13 @@
 use async_graphql::{Request, Response};
 use linera_sdk::{
     graphql::GraphQLMutationRoot,
-    linera_base_types::{ContractAbi, ServiceAbi},
+    linera_base_types::{
+        Account, AccountOwner, ChainId, ContractAbi, CryptoHash, ServiceAbi, TimeDelta,
+    },
 };
 use serde::{Deserialize, Serialize};
 
+pub mod rules;
 pub mod state;
 
 pub struct JeteeahAbi;
@@ -21,17 +24,316 @@ impl ServiceAbi for JeteeahAbi {
 
 #[derive(Debug, Deserialize, Serialize, GraphQLMutationRoot)]
 pub enum Operation {
-    Increment { value: u64 },
+    Increment {
+        value: u64,
+    },
     StartGame,
-    MoveSnake { direction: state::Direction },
+    MoveSnake {
+        direction: state::Direction,
+    },
     EatFood,
     EndGame,
     ResetGame,
-    AddPoints { amount: u64 },
-    RedeemPoints { amount: u64 },
-    SetGameParameters { width: i32, height: i32 },
-    UpdateFoodSpawnRate { rate: u64 },
+    AddPoints {
+        target: AccountOwner,
+        amount: u64,
+    },
+    RedeemPoints {
+        amount: u64,
+    },
+    SetGameParameters {
+        width: i32,
+        height: i32,
+    },
+    UpdateFoodSpawnRate {
+        rate: u64,
+    },
     ResetLeaderboard,
     PauseGame,
     ResumeGame,
+    EmergencyPause {
+        reason: String,
+    },
+    LiftEmergencyPause,
+    TransferPoints {
+        to: Account,
+        amount: u64,
+    },
+    CreateReward {
+        name: String,
+        price: u64,
+        stock: Option<u64>,
+        kind: state::RewardKind,
+        payout: Option<state::RewardPayout>,
+    },
+    UpdateReward {
+        reward_id: u64,
+        name: String,
+        price: u64,
+        stock: Option<u64>,
+        kind: state::RewardKind,
+    },
+    RetireReward {
+        reward_id: u64,
+    },
+    RedeemReward {
+        reward_id: u64,
+    },
+    EquipSkin {
+        skin_id: u32,
+    },
+    ConfigureToken {
+        config: Option<state::TokenConfig>,
+    },
+    StartRankedGame,
+    ClaimDaily,
+    SetDailyRewardSchedule {
+        schedule: Vec<u64>,
+    },
+    Revive,
+    SetReviveConfig {
+        config: state::ReviveConfig,
+    },
+    CreateMap {
+        name: String,
+        width: i32,
+        height: i32,
+        min_level: u32,
+    },
+    StartGameOnMap {
+        map_id: u32,
+    },
+    DefineAchievement {
+        details: state::AchievementDetails,
+    },
+    RemoveAchievement {
+        achievement_id: u32,
+    },
+    SetProgressionConfig {
+        config: state::ProgressionConfig,
+    },
+    SubmitRun {
+        game_id: u64,
+        inputs: Vec<state::Direction>,
+    },
+    SetRunStepLimit {
+        limit: u64,
+    },
+    SetMaxSessionDuration {
+        duration: Option<TimeDelta>,
+    },
+    ExpireStaleGames {
+        limit: u32,
+    },
+    SetExpiryConfig {
+        config: state::ExpiryConfig,
+    },
+    CreateRoom {
+        capacity: u32,
+        commit_reveal: bool,
+        stake: u64,
+    },
+    JoinRoom {
+        code: String,
+    },
+    JoinRemoteRoom {
+        host_chain: ChainId,
+        code: String,
+    },
+    LeaveRoom,
+    ToggleReady,
+    StartRoom,
+    SubmitMove {
+        tick: u64,
+        direction: state::Direction,
+    },
+    CommitMove {
+        tick: u64,
+        commitment: CryptoHash,
+    },
+    RevealMove {
+        tick: u64,
+        direction: state::Direction,
+        salt: String,
+    },
+    ResolveTick {
+        room_id: u64,
+    },
+    SetRoomConfig {
+        config: state::RoomConfig,
+    },
+    WithdrawRake {
+        amount: u64,
+    },
+    IssueChallenge {
+        opponent: AccountOwner,
+        game_id: u64,
+        stake: u64,
+    },
+    AcceptChallenge {
+        challenge_id: u64,
+    },
+    DeclineChallenge {
+        challenge_id: u64,
+    },
+    CancelChallenge {
+        challenge_id: u64,
+    },
+    CreateTournament {
+        details: state::TournamentDetails,
+    },
+    RegisterForTournament {
+        tournament_id: u64,
+    },
+    StartTournamentGame {
+        tournament_id: u64,
+    },
+    FinalizeTournament {
+        tournament_id: u64,
+    },
+}
+
+/// Cross-chain messages between instances of the application
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum Message {
+    /// Credits points sent by the message's authenticated signer
+    Credit { target: AccountOwner, amount: u64 },
+    /// A room operation by the message's authenticated signer, sent from their own
+    /// chain to the chain hosting their room
+    RoomAction { action: RoomAction },
+    /// The latest state of a room, sent by its host chain to its members' chains and
+    /// to those of the players who just left it
+    RoomUpdate {
+        room: state::Room,
+        board: Option<state::Board>,
+        departed: Vec<AccountOwner>,
+    },
+}
+
+/// Room operations a player can perform on another chain's room
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum RoomAction {
+    Join {
+        code: String,
+    },
+    Leave,
+    ToggleReady,
+    Start,
+    SubmitMove {
+        tick: u64,
+        direction: state::Direction,
+    },
+    CommitMove {
+        tick: u64,
+        commitment: CryptoHash,
+    },
+    RevealMove {
+        tick: u64,
+        direction: state::Direction,
+        salt: String,
+    },
+}
+
+/// The stream player progression events are published on
+pub const PROGRESSION_STREAM: &str = "progression";
+
+/// Events published by the application
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Event {
+    /// A player reached a new level
+    LevelUp { player: AccountOwner, level: u32 },
+}
+
+impl Operation {
+    /// The room action to forward when the operation is performed by a player whose
+    /// room is hosted on another chain
+    pub fn room_action(&self) -> Option<RoomAction> {
+        match self {
+            Operation::LeaveRoom => Some(RoomAction::Leave),
+            Operation::ToggleReady => Some(RoomAction::ToggleReady),
+            Operation::StartRoom => Some(RoomAction::Start),
+            Operation::SubmitMove { tick, direction } => Some(RoomAction::SubmitMove {
+                tick: *tick,
+                direction: *direction,
+            }),
+            Operation::CommitMove { tick, commitment } => Some(RoomAction::CommitMove {
+                tick: *tick,
+                commitment: *commitment,
+            }),
+            Operation::RevealMove {
+                tick,
+                direction,
+                salt,
+            } => Some(RoomAction::RevealMove {
+                tick: *tick,
+                direction: *direction,
+                salt: salt.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether the operation touches gameplay or points, and must therefore be
+    /// rejected while the application is under an emergency pause
+    pub fn is_gameplay(&self) -> bool {
+        match self {
+            Operation::StartGame
+            | Operation::MoveSnake { .. }
+            | Operation::EatFood
+            | Operation::EndGame
+            | Operation::ResetGame
+            | Operation::AddPoints { .. }
+            | Operation::RedeemPoints { .. }
+            | Operation::PauseGame
+            | Operation::ResumeGame
+            | Operation::TransferPoints { .. }
+            | Operation::RedeemReward { .. }
+            | Operation::EquipSkin { .. }
+            | Operation::StartRankedGame
+            | Operation::ClaimDaily
+            | Operation::Revive
+            | Operation::StartGameOnMap { .. }
+            | Operation::SubmitRun { .. }
+            | Operation::ExpireStaleGames { .. }
+            | Operation::CreateRoom { .. }
+            | Operation::JoinRoom { .. }
+            | Operation::JoinRemoteRoom { .. }
+            | Operation::LeaveRoom
+            | Operation::ToggleReady
+            | Operation::StartRoom
+            | Operation::SubmitMove { .. }
+            | Operation::CommitMove { .. }
+            | Operation::RevealMove { .. }
+            | Operation::ResolveTick { .. }
+            | Operation::WithdrawRake { .. }
+            | Operation::IssueChallenge { .. }
+            | Operation::AcceptChallenge { .. }
+            | Operation::DeclineChallenge { .. }
+            | Operation::CancelChallenge { .. }
+            | Operation::RegisterForTournament { .. }
+            | Operation::StartTournamentGame { .. }
+            | Operation::FinalizeTournament { .. } => true,
+            Operation::Increment { .. }
+            | Operation::SetGameParameters { .. }
+            | Operation::UpdateFoodSpawnRate { .. }
+            | Operation::ResetLeaderboard
+            | Operation::EmergencyPause { .. }
+            | Operation::LiftEmergencyPause
+            | Operation::CreateReward { .. }
+            | Operation::UpdateReward { .. }
+            | Operation::RetireReward { .. }
+            | Operation::ConfigureToken { .. }
+            | Operation::SetDailyRewardSchedule { .. }
+            | Operation::SetReviveConfig { .. }
+            | Operation::CreateMap { .. }
+            | Operation::DefineAchievement { .. }
+            | Operation::RemoveAchievement { .. }
+            | Operation::SetProgressionConfig { .. }
+            | Operation::SetRunStepLimit { .. }
+            | Operation::SetMaxSessionDuration { .. }
+            | Operation::SetExpiryConfig { .. }
+            | Operation::SetRoomConfig { .. }
+            | Operation::CreateTournament { .. } => false,
+        }
+    }
 }
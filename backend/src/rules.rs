@@ -0,0 +1,377 @@
+//! The deterministic snake rules for solo games and rooms' shared boards, used by the
+//! contract and by replays.
+//!
+//! Everything here depends only on the game or board and its seed, so running the same
+//! inputs from the same start always produces the same game.
+
+use linera_sdk::linera_base_types::{AccountOwner, Timestamp};
+
+use crate::state::{
+    Board, Direction, EndCause, GameState, GameStatus, Position, RoomResult, Snake, TickPhase,
+};
+
+/// Points scored for each piece of food
+pub const FOOD_SCORE: u64 = 10;
+
+/// What a single input did to the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    /// The input reversed the snake into itself and was dropped
+    Ignored,
+    Moved,
+    /// The snake moved onto the food and grew
+    Ate,
+    /// The snake would have hit something; the board is left as it was
+    Crashed(EndCause),
+}
+
+/// A fresh game with a three-segment snake in the middle of the board, heading right
+///
+/// The bookkeeping fields are left at their defaults for the caller to fill in
+pub fn new_game(id: u64, seed: u64, width: i32, height: i32) -> GameState {
+    let center_x = width / 2;
+    let center_y = height / 2;
+
+    GameState {
+        id,
+        snake_body: (0..3)
+            .map(|offset| Position {
+                x: center_x - offset,
+                y: center_y,
+            })
+            .collect(),
+        direction: Direction::Right,
+        food_position: Position {
+            x: (center_x + 5) % width,
+            y: (center_y + 5) % height,
+        },
+        score: 0,
+        status: GameStatus::Active,
+        end_cause: None,
+        crashed_at: None,
+        revive_count: 0,
+        width,
+        height,
+        ranked: false,
+        skin_id: 0,
+        map_id: 0,
+        straight_moves: 0,
+        longest_straight: 0,
+        steps: 0,
+        started_at: Timestamp::from(0),
+        last_move_at: None,
+        ended_at: None,
+        last_active_at: Timestamp::from(0),
+        paused_at: None,
+        config_version: 0,
+        seed,
+        challenge_id: None,
+        tournament_id: None,
+    }
+}
+
+/// Derives the seed a game's food placement follows
+pub fn seed_for(game_id: u64, started_at: Timestamp) -> u64 {
+    mix(game_id ^ mix(started_at.micros()))
+}
+
+/// Turns the snake and advances it one cell
+pub fn step(game: &mut GameState, direction: Direction) -> StepOutcome {
+    if direction == opposite(game.direction) {
+        return StepOutcome::Ignored;
+    }
+
+    let new_head = advance(&game.snake_body[0], direction);
+
+    // The fatal step is not applied, so a revive can pick up from here
+    if let Some(cause) = collision(game, &new_head) {
+        return StepOutcome::Crashed(cause);
+    }
+
+    if direction == game.direction {
+        game.straight_moves += 1;
+    } else {
+        game.straight_moves = 1;
+    }
+    game.longest_straight = game.longest_straight.max(game.straight_moves);
+    game.direction = direction;
+    game.steps += 1;
+
+    let ate_food = new_head.x == game.food_position.x && new_head.y == game.food_position.y;
+    game.snake_body.insert(0, new_head);
+    if !ate_food {
+        game.snake_body.pop();
+        return StepOutcome::Moved;
+    }
+
+    game.score += FOOD_SCORE;
+    game.food_position = next_food(game);
+    StepOutcome::Ate
+}
+
+/// What the snake would hit by moving its head to `position`, if anything
+pub fn collision(game: &GameState, position: &Position) -> Option<EndCause> {
+    if position.x < 0 || position.x >= game.width || position.y < 0 || position.y >= game.height {
+        return Some(EndCause::WallCollision);
+    }
+
+    if game
+        .snake_body
+        .iter()
+        .any(|segment| segment.x == position.x && segment.y == position.y)
+    {
+        return Some(EndCause::SelfCollision);
+    }
+
+    None
+}
+
+/// Picks where the next food appears, avoiding the snake where possible
+pub fn next_food(game: &GameState) -> Position {
+    let cells = (game.width as u64 * game.height as u64).max(1);
+    let mut value = mix(game.seed ^ game.steps);
+    let mut candidate = Position { x: 0, y: 0 };
+
+    // Bounded so a board full of snake can't loop forever
+    for _ in 0..64 {
+        let cell = value % cells;
+        candidate = Position {
+            x: (cell % game.width as u64) as i32,
+            y: (cell / game.width as u64) as i32,
+        };
+        if !game
+            .snake_body
+            .iter()
+            .any(|segment| segment.x == candidate.x && segment.y == candidate.y)
+        {
+            break;
+        }
+        value = mix(value);
+    }
+
+    candidate
+}
+
+/// A shared board for a room's players, with snakes spread over separate rows and
+/// alternately heading in from the left and right edges
+pub fn new_board(
+    room_id: u64,
+    seed: u64,
+    width: i32,
+    height: i32,
+    players: &[AccountOwner],
+) -> Board {
+    let rows = players.len() as i32 + 1;
+    let snakes = players
+        .iter()
+        .enumerate()
+        .map(|(index, player)| {
+            let y = (index as i32 + 1) * height / rows;
+            let (direction, head_x, step) = if index % 2 == 0 {
+                (Direction::Right, 2, -1)
+            } else {
+                (Direction::Left, width - 3, 1)
+            };
+            Snake {
+                player: *player,
+                body: (0..3)
+                    .map(|offset| Position {
+                        x: head_x + offset * step,
+                        y,
+                    })
+                    .collect(),
+                direction,
+                next_direction: None,
+                commitment: None,
+                missed_reveals: 0,
+                score: 0,
+                alive: true,
+                end_cause: None,
+                eliminated_at: None,
+            }
+        })
+        .collect();
+
+    let mut board = Board {
+        room_id,
+        width,
+        height,
+        snakes,
+        food_position: Position { x: 0, y: 0 },
+        tick: 0,
+        tick_deadline: Timestamp::from(0),
+        seed,
+        commit_reveal: false,
+        phase: TickPhase::Submit,
+    };
+    board.food_position = next_board_food(&board);
+    board
+}
+
+/// Moves every living snake on the board at once. `moves[i]` steers `board.snakes[i]`;
+/// a missing or reversing move keeps the snake going straight
+///
+/// Snakes that hit a wall, themselves or another snake's body are knocked out, and
+/// snakes whose heads meet, by moving into the same cell or through each other, are
+/// all knocked out together
+pub fn step_board(board: &mut Board, moves: &[Option<Direction>]) {
+    let tick = board.tick + 1;
+    let alive = (0..board.snakes.len())
+        .filter(|&index| board.snakes[index].alive)
+        .collect::<Vec<_>>();
+
+    // Every snake moves before any collision is judged
+    let mut eaten = vec![false; board.snakes.len()];
+    for &index in &alive {
+        let snake = &mut board.snakes[index];
+        if let Some(direction) = moves.get(index).copied().flatten() {
+            if direction != opposite(snake.direction) {
+                snake.direction = direction;
+            }
+        }
+        let head = advance(&snake.body[0], snake.direction);
+        eaten[index] = head == board.food_position;
+        snake.body.insert(0, head);
+        if !eaten[index] {
+            snake.body.pop();
+        }
+    }
+
+    let mut knocked_out = Vec::new();
+    for &index in &alive {
+        let snake = &board.snakes[index];
+        let head = &snake.body[0];
+        let cause = if head.x < 0 || head.x >= board.width || head.y < 0 || head.y >= board.height {
+            EndCause::WallCollision
+        } else if alive.iter().any(|&other| {
+            let other_body = &board.snakes[other].body;
+            other != index
+                && (other_body[0] == *head
+                    || (other_body[0] == snake.body[1] && other_body[1] == *head))
+        }) {
+            EndCause::HeadOn
+        } else if snake.body[1..].contains(head) {
+            EndCause::SelfCollision
+        } else if alive
+            .iter()
+            .any(|&other| other != index && board.snakes[other].body[1..].contains(head))
+        {
+            EndCause::SnakeCollision
+        } else {
+            continue;
+        };
+        knocked_out.push((index, cause));
+    }
+
+    for (index, cause) in knocked_out {
+        let snake = &mut board.snakes[index];
+        snake.alive = false;
+        snake.end_cause = Some(cause);
+        snake.eliminated_at = Some(tick);
+    }
+
+    let mut food_taken = false;
+    for &index in &alive {
+        if eaten[index] && board.snakes[index].alive {
+            board.snakes[index].score += FOOD_SCORE;
+            food_taken = true;
+        }
+    }
+
+    board.tick = tick;
+    if food_taken {
+        board.food_position = next_board_food(board);
+    }
+}
+
+/// Whether the board's game is decided, with at most one snake left
+pub fn is_board_over(board: &Board) -> bool {
+    board.snakes.iter().filter(|snake| snake.alive).count() <= 1
+}
+
+/// Final standings: survivors first, then by how long each snake lasted. Snakes
+/// knocked out on the same tick tie
+pub fn room_results(board: &Board) -> Vec<RoomResult> {
+    // A survivor outlasts every knocked-out snake
+    let lasted = |snake: &Snake| snake.eliminated_at.unwrap_or(u64::MAX);
+
+    board
+        .snakes
+        .iter()
+        .map(|snake| RoomResult {
+            player: snake.player,
+            score: snake.score,
+            placement: 1 + board
+                .snakes
+                .iter()
+                .filter(|other| lasted(other) > lasted(snake))
+                .count() as u32,
+            eliminated_at: snake.eliminated_at,
+            payout: 0,
+        })
+        .collect()
+}
+
+/// Picks where the board's next food appears, avoiding every snake where possible
+fn next_board_food(board: &Board) -> Position {
+    let cells = (board.width as u64 * board.height as u64).max(1);
+    let mut value = mix(board.seed ^ board.tick);
+    let mut candidate = Position { x: 0, y: 0 };
+
+    // Bounded so a crowded board can't loop forever
+    for _ in 0..64 {
+        let cell = value % cells;
+        candidate = Position {
+            x: (cell % board.width as u64) as i32,
+            y: (cell / board.width as u64) as i32,
+        };
+        if !board
+            .snakes
+            .iter()
+            .any(|snake| snake.alive && snake.body.contains(&candidate))
+        {
+            break;
+        }
+        value = mix(value);
+    }
+
+    candidate
+}
+
+fn advance(head: &Position, direction: Direction) -> Position {
+    match direction {
+        Direction::Up => Position {
+            x: head.x,
+            y: head.y - 1,
+        },
+        Direction::Down => Position {
+            x: head.x,
+            y: head.y + 1,
+        },
+        Direction::Left => Position {
+            x: head.x - 1,
+            y: head.y,
+        },
+        Direction::Right => Position {
+            x: head.x + 1,
+            y: head.y,
+        },
+    }
+}
+
+fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::Up => Direction::Down,
+        Direction::Down => Direction::Up,
+        Direction::Left => Direction::Right,
+        Direction::Right => Direction::Left,
+    }
+}
+
+/// SplitMix64, a small and well-distributed mixing function
+pub fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
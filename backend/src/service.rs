@@ -1,21 +1,28 @@
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
-mod state;
-
 use std::sync::Arc;
 
-use async_graphql::{EmptySubscription, Object, Schema};
+use async_graphql::{EmptySubscription, Object, OneofObject, Schema, SimpleObject};
 use linera_sdk::{
-    graphql::GraphQLMutationRoot, linera_base_types::WithServiceAbi, views::View, Service,
-    ServiceRuntime,
+    graphql::GraphQLMutationRoot,
+    linera_base_types::{AccountOwner, CryptoHash, TimeDelta, Timestamp, WithServiceAbi},
+    views::View,
+    Service, ServiceRuntime,
 };
 
-use jeteeah::Operation;
-
-use self::state::JeteeahState;
+use jeteeah::{
+    rules::{self, StepOutcome},
+    state::{
+        Achievement, Board, Challenge, ChallengeStatus, DailyStreak, Direction, EndCause, Escrow,
+        GameMap, GameState, GameStatus, GameSummary, InventoryItem, JeteeahState, LeaderboardEntry,
+        LedgerEntry, MoveCommitment, Position, RemoteRoom, Reward, Room, RoomStatus, TickPhase,
+        Tournament, TournamentStanding, DEFAULT_SKIN_ID,
+    },
+    Operation,
+};
 
 pub struct JeteeahService {
-    state: JeteeahState,
+    state: Arc<JeteeahState>,
     runtime: Arc<ServiceRuntime<Self>>,
 }
 
@@ -33,7 +40,7 @@ impl Service for JeteeahService {
             .await
             .expect("Failed to load state");
         JeteeahService {
-            state,
+            state: Arc::new(state),
             runtime: Arc::new(runtime),
         }
     }
@@ -41,7 +48,8 @@ impl Service for JeteeahService {
     async fn handle_query(&self, query: Self::Query) -> Self::QueryResponse {
         Schema::build(
             QueryRoot {
-                value: *self.state.value.get(),
+                state: self.state.clone(),
+                runtime: self.runtime.clone(),
             },
             Operation::mutation_root(self.runtime.clone()),
             EmptySubscription,
@@ -53,13 +61,665 @@ impl Service for JeteeahService {
 }
 
 struct QueryRoot {
-    value: u64,
+    state: Arc<JeteeahState>,
+    runtime: Arc<ServiceRuntime<JeteeahService>>,
+}
+
+/// A player's login streak as seen right now
+#[derive(SimpleObject)]
+struct DailyStatus {
+    /// Consecutive days claimed, or zero once a day was missed
+    streak: u32,
+    /// Whether today's reward is still available
+    can_claim: bool,
+    next_claim_time: Timestamp,
+}
+
+/// The board of a replayed game after a number of inputs
+#[derive(SimpleObject)]
+struct ReplayFrame {
+    /// How many inputs have been applied; frame zero is the starting board
+    index: u64,
+    /// The input that produced this frame
+    input: Option<Direction>,
+    snake_body: Vec<Position>,
+    direction: Direction,
+    food_position: Position,
+    score: u64,
+    /// Set when the input crashed the snake; later frames follow a revive
+    crash: Option<EndCause>,
+}
+
+impl ReplayFrame {
+    fn new(
+        index: u64,
+        input: Option<Direction>,
+        game: &GameState,
+        crash: Option<EndCause>,
+    ) -> Self {
+        ReplayFrame {
+            index,
+            input,
+            snake_body: game.snake_body.clone(),
+            direction: game.direction,
+            food_position: game.food_position.clone(),
+            score: game.score,
+            crash,
+        }
+    }
+}
+
+/// The tick a room is collecting moves for
+#[derive(SimpleObject)]
+struct RoomTick {
+    tick: u64,
+    /// Whether moves, or commitments, are being collected or revealed
+    phase: TickPhase,
+    /// When the phase ends even with moves missing
+    deadline: Timestamp,
+    /// Players the phase is still waiting for
+    pending_players: Vec<AccountOwner>,
+}
+
+/// A player's lasting progression
+#[derive(SimpleObject)]
+struct PlayerProfile {
+    xp: u64,
+    level: u32,
+    /// The total XP needed for the next level, or `None` at the top of the curve
+    next_level_xp: Option<u64>,
+    /// How much more XP the next level needs
+    xp_to_next_level: Option<u64>,
+    games_played: u64,
+    /// Time spent across all settled games
+    play_time: TimeDelta,
+}
+
+/// An achievement and whether a player has unlocked it
+#[derive(SimpleObject)]
+struct PlayerAchievement {
+    achievement: Achievement,
+    /// When the player unlocked it, or `None` while it's still pending
+    unlocked_at: Option<Timestamp>,
+}
+
+/// A room, or a player, to watch
+#[derive(OneofObject)]
+enum SpectateTarget {
+    RoomId(u64),
+    Player(AccountOwner),
+}
+
+/// What a spectator sees of a room or solo game in progress
+#[derive(SimpleObject)]
+struct LiveView {
+    room: Option<Room>,
+    board: Option<Board>,
+    /// The room's boards after its latest ticks, oldest first
+    recent_boards: Vec<Board>,
+    game: Option<GameState>,
+    /// The solo game's latest frames, oldest first
+    recent_frames: Vec<ReplayFrame>,
+}
+
+impl QueryRoot {
+    /// Re-runs a game's recorded inputs from its starting board, up to the given frame
+    async fn replay_frames(
+        &self,
+        mut game: GameState,
+        until: Option<u64>,
+    ) -> async_graphql::Result<Vec<ReplayFrame>> {
+        let inputs = match self.state.replay_inputs.try_load_entry(&game.id).await? {
+            Some(log) => log.read(..).await?,
+            None => Vec::new(),
+        };
+
+        let mut frames = vec![ReplayFrame::new(0, None, &game, None)];
+        for (index, input) in (1..).zip(inputs) {
+            if until.is_some_and(|until| until < index) {
+                break;
+            }
+            let crash = match rules::step(&mut game, input) {
+                StepOutcome::Crashed(cause) => Some(cause),
+                _ => None,
+            };
+            frames.push(ReplayFrame::new(index, Some(input), &game, crash));
+        }
+        Ok(frames)
+    }
+
+    /// The challenges a player issued or received, newest first
+    async fn challenges_of(&self, player: &AccountOwner) -> async_graphql::Result<Vec<Challenge>> {
+        let Some(log) = self.state.player_challenges.try_load_entry(player).await? else {
+            return Ok(Vec::new());
+        };
+        let mut challenges = Vec::new();
+        for challenge_id in log.read(..).await?.into_iter().rev() {
+            challenges.extend(self.state.challenges.get(&challenge_id).await?);
+        }
+        Ok(challenges)
+    }
+
+    /// A room as spectators see it, with up to `frames` of its latest boards
+    async fn live_room(
+        &self,
+        room_id: u64,
+        frames: usize,
+    ) -> async_graphql::Result<Option<LiveView>> {
+        let Some(room) = self.state.rooms.get(&room_id).await? else {
+            return Ok(None);
+        };
+        let mut recent_boards = self
+            .state
+            .room_frames
+            .get(&room_id)
+            .await?
+            .unwrap_or_default();
+        recent_boards.drain(..recent_boards.len().saturating_sub(frames));
+
+        Ok(Some(LiveView {
+            room: Some(room),
+            board: self.state.room_boards.get(&room_id).await?,
+            recent_boards,
+            game: None,
+            recent_frames: Vec::new(),
+        }))
+    }
 }
 
 #[Object]
 impl QueryRoot {
     async fn value(&self) -> &u64 {
-        &self.value
+        self.state.value.get()
+    }
+
+    /// Whether gameplay is frozen by an emergency pause
+    async fn paused(&self) -> bool {
+        *self.state.paused.get()
+    }
+
+    /// The reason given for the current emergency pause, if any
+    async fn pause_reason(&self) -> Option<String> {
+        self.state.pause_reason.get().clone()
+    }
+
+    /// How many games are running, paused or waiting on a revive
+    async fn active_game_count(&self) -> async_graphql::Result<usize> {
+        Ok(self.state.active_players.count().await?)
+    }
+
+    /// The best scores, highest first, with the revives used to reach them
+    async fn leaderboard(
+        &self,
+        #[graphql(default = 10)] limit: usize,
+    ) -> async_graphql::Result<Vec<LeaderboardEntry>> {
+        let mut scores = self.state.high_scores.index_values().await?;
+        scores.sort_by(|(_, a), (_, b)| b.cmp(a));
+        scores.truncate(limit);
+
+        let mut entries = Vec::with_capacity(scores.len());
+        for (player, score) in scores {
+            let revives = self
+                .state
+                .high_score_revives
+                .get(&player)
+                .await?
+                .unwrap_or(0);
+            entries.push(LeaderboardEntry {
+                player: player.to_string(),
+                score,
+                revives,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// A page of a player's points ledger, newest entries first
+    async fn points_history(
+        &self,
+        player: AccountOwner,
+        #[graphql(default = 0)] offset: usize,
+        #[graphql(default = 20)] limit: usize,
+    ) -> async_graphql::Result<Vec<LedgerEntry>> {
+        let Some(history) = self.state.points_history.try_load_entry(&player).await? else {
+            return Ok(Vec::new());
+        };
+        let end = history.count().saturating_sub(offset);
+        let start = end.saturating_sub(limit);
+        let mut entries = history.read(start..end).await?;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// A player's settled games, newest first
+    async fn match_history(
+        &self,
+        player: AccountOwner,
+        #[graphql(default = 0)] offset: usize,
+        #[graphql(default = 20)] limit: usize,
+    ) -> async_graphql::Result<Vec<GameSummary>> {
+        let Some(history) = self.state.game_history.try_load_entry(&player).await? else {
+            return Ok(Vec::new());
+        };
+        let end = history.count().saturating_sub(offset);
+        let start = end.saturating_sub(limit);
+        let mut games = Vec::new();
+        for game_id in history.read(start..end).await?.into_iter().rev() {
+            games.extend(self.state.archived_games.get(&game_id).await?);
+        }
+        Ok(games)
+    }
+
+    /// The summary of a settled game
+    async fn game(&self, game_id: u64) -> async_graphql::Result<Option<GameSummary>> {
+        Ok(self.state.archived_games.get(&game_id).await?)
+    }
+
+    /// Replays a settled game from its seed and inputs, returning every frame or only
+    /// the chosen one. Runs raced in an undecided challenge stay hidden until it's over
+    async fn replay(
+        &self,
+        game_id: u64,
+        frame: Option<u64>,
+    ) -> async_graphql::Result<Vec<ReplayFrame>> {
+        let summary = self
+            .state
+            .archived_games
+            .get(&game_id)
+            .await?
+            .ok_or("Game not found")?;
+        let challenged = self
+            .challenges_of(&summary.player)
+            .await?
+            .iter()
+            .any(|challenge| {
+                challenge.game_id == game_id
+                    && matches!(
+                        challenge.status,
+                        ChallengeStatus::Pending | ChallengeStatus::Accepted
+                    )
+            });
+        if challenged {
+            return Err("The game is being raced in a challenge".into());
+        }
+        let game = rules::new_game(game_id, summary.seed, summary.width, summary.height);
+        let mut frames = self.replay_frames(game, frame).await?;
+
+        if let Some(frame) = frame {
+            frames.retain(|replayed| replayed.index == frame);
+        }
+        Ok(frames)
+    }
+
+    /// The reward catalog, optionally including retired entries
+    async fn rewards(
+        &self,
+        #[graphql(default = false)] include_retired: bool,
+    ) -> async_graphql::Result<Vec<Reward>> {
+        Ok(self
+            .state
+            .rewards
+            .index_values()
+            .await?
+            .into_iter()
+            .map(|(_, reward)| reward)
+            .filter(|reward| include_retired || !reward.retired)
+            .collect())
+    }
+
+    /// The skins a player may equip, including the default one
+    async fn owned_skins(&self, player: AccountOwner) -> async_graphql::Result<Vec<u32>> {
+        let mut skins = vec![DEFAULT_SKIN_ID];
+        if let Some(owned) = self.state.owned_skins.try_load_entry(&player).await? {
+            skins.extend(owned.indices().await?);
+        }
+        Ok(skins)
+    }
+
+    /// The skin a player's next game will use
+    async fn equipped_skin(&self, player: AccountOwner) -> async_graphql::Result<u32> {
+        Ok(self
+            .state
+            .equipped_skins
+            .get(&player)
+            .await?
+            .unwrap_or(DEFAULT_SKIN_ID))
+    }
+
+    /// A player's daily login streak and when they can claim next
+    async fn daily_status(&self, player: AccountOwner) -> async_graphql::Result<DailyStatus> {
+        let daily = self
+            .state
+            .daily_streaks
+            .get(&player)
+            .await?
+            .unwrap_or_default();
+        let now = self.runtime.system_time();
+        let next_claim_time = daily.next_claim_time();
+
+        Ok(DailyStatus {
+            streak: daily.current_streak(DailyStreak::day_of(now)),
+            can_claim: now >= next_claim_time,
+            next_claim_time,
+        })
+    }
+
+    /// A player's XP, level and distance to the next level, and their time played
+    async fn profile(&self, player: AccountOwner) -> async_graphql::Result<PlayerProfile> {
+        let xp = self.state.xp.get(&player).await?.unwrap_or(0);
+        let progression = self.state.progression.get();
+        let level = progression.level_for(xp);
+        let next_level_xp = progression.next_level_xp(level);
+
+        Ok(PlayerProfile {
+            xp,
+            level,
+            next_level_xp,
+            xp_to_next_level: next_level_xp.map(|needed| needed - xp),
+            games_played: self.state.games_played.get(&player).await?.unwrap_or(0),
+            play_time: self
+                .state
+                .play_time
+                .get(&player)
+                .await?
+                .unwrap_or(TimeDelta::ZERO),
+        })
+    }
+
+    /// The boards players can start games on, starting with the default one
+    async fn maps(&self) -> async_graphql::Result<Vec<GameMap>> {
+        let mut maps = vec![GameMap::classic(
+            *self.state.game_width.get(),
+            *self.state.game_height.get(),
+        )];
+        maps.extend(
+            self.state
+                .maps
+                .index_values()
+                .await?
+                .into_iter()
+                .map(|(_, map)| map),
+        );
+        Ok(maps)
+    }
+
+    /// Every achievement, with when the player unlocked it
+    async fn achievements(
+        &self,
+        player: AccountOwner,
+    ) -> async_graphql::Result<Vec<PlayerAchievement>> {
+        let unlocked = self
+            .state
+            .unlocked_achievements
+            .try_load_entry(&player)
+            .await?;
+        let mut achievements = Vec::new();
+        for (id, achievement) in self.state.achievements.index_values().await? {
+            let unlocked_at = match &unlocked {
+                Some(unlocked) => unlocked.get(&id).await?,
+                None => None,
+            };
+            achievements.push(PlayerAchievement {
+                achievement,
+                unlocked_at,
+            });
+        }
+        Ok(achievements)
+    }
+
+    /// A multiplayer room by id
+    async fn room(&self, room_id: u64) -> async_graphql::Result<Option<Room>> {
+        Ok(self.state.rooms.get(&room_id).await?)
+    }
+
+    /// The shared board of a room that has started
+    async fn room_board(&self, room_id: u64) -> async_graphql::Result<Option<Board>> {
+        Ok(self.state.room_boards.get(&room_id).await?)
+    }
+
+    /// Rooms whose game is being played right now
+    async fn live_rooms(&self) -> async_graphql::Result<Vec<Room>> {
+        let mut rooms = Vec::new();
+        for room_id in self.state.playing_rooms.indices().await? {
+            rooms.extend(self.state.rooms.get(&room_id).await?);
+        }
+        Ok(rooms)
+    }
+
+    /// The highest-scoring solo games being played right now
+    async fn live_games(
+        &self,
+        #[graphql(default = 10)] limit: usize,
+    ) -> async_graphql::Result<Vec<GameState>> {
+        let mut games = Vec::new();
+        for player in self.state.active_players.indices().await? {
+            if let Some(game) = self.state.games.get(&player).await? {
+                if game.status == GameStatus::Active {
+                    games.push(game);
+                }
+            }
+        }
+        games.sort_by(|a, b| b.score.cmp(&a.score));
+        games.truncate(limit);
+        Ok(games)
+    }
+
+    /// Watches a room, or a player: their room while its game is on, otherwise their
+    /// unsettled solo game. Includes up to `frames` of the latest boards or moves
+    async fn spectate(
+        &self,
+        target: SpectateTarget,
+        #[graphql(default = 10)] frames: usize,
+    ) -> async_graphql::Result<Option<LiveView>> {
+        let player = match target {
+            SpectateTarget::RoomId(room_id) => return self.live_room(room_id, frames).await,
+            SpectateTarget::Player(player) => player,
+        };
+
+        if let Some(room_id) = self.state.player_rooms.get(&player).await? {
+            if self.state.playing_rooms.contains(&room_id).await? {
+                return self.live_room(room_id, frames).await;
+            }
+        }
+
+        let Some(game) = self.state.games.get(&player).await? else {
+            return Ok(None);
+        };
+        if game.status == GameStatus::Settled {
+            return Ok(None);
+        }
+        let start = rules::new_game(game.id, game.seed, game.width, game.height);
+        let mut recent_frames = self.replay_frames(start, None).await?;
+        recent_frames.drain(..recent_frames.len().saturating_sub(frames));
+
+        Ok(Some(LiveView {
+            room: None,
+            board: None,
+            recent_boards: Vec::new(),
+            game: Some(game),
+            recent_frames,
+        }))
+    }
+
+    /// Challenges a player issued that haven't been decided yet, newest first
+    async fn pending_challenges(
+        &self,
+        player: AccountOwner,
+    ) -> async_graphql::Result<Vec<Challenge>> {
+        let mut challenges = self.challenges_of(&player).await?;
+        challenges.retain(|challenge| {
+            challenge.challenger == player
+                && matches!(
+                    challenge.status,
+                    ChallengeStatus::Pending | ChallengeStatus::Accepted
+                )
+        });
+        Ok(challenges)
+    }
+
+    /// Challenges waiting for a player to accept or decline them, newest first
+    async fn received_challenges(
+        &self,
+        player: AccountOwner,
+    ) -> async_graphql::Result<Vec<Challenge>> {
+        let mut challenges = self.challenges_of(&player).await?;
+        challenges.retain(|challenge| {
+            challenge.opponent == player && challenge.status == ChallengeStatus::Pending
+        });
+        Ok(challenges)
+    }
+
+    /// A player's decided challenges on either side, newest first
+    async fn completed_challenges(
+        &self,
+        player: AccountOwner,
+    ) -> async_graphql::Result<Vec<Challenge>> {
+        let mut challenges = self.challenges_of(&player).await?;
+        challenges.retain(|challenge| challenge.status == ChallengeStatus::Completed);
+        Ok(challenges)
+    }
+
+    /// Tournaments that haven't been finalized yet, whether upcoming or running
+    async fn tournaments(&self) -> async_graphql::Result<Vec<Tournament>> {
+        let mut tournaments = Vec::new();
+        for (_, tournament) in self.state.tournaments.index_values().await? {
+            if tournament.finalized_at.is_none() {
+                tournaments.push(tournament);
+            }
+        }
+        Ok(tournaments)
+    }
+
+    async fn tournament(&self, tournament_id: u64) -> async_graphql::Result<Option<Tournament>> {
+        Ok(self.state.tournaments.get(&tournament_id).await?)
+    }
+
+    /// A tournament's ranking: the live one while it runs, the final one afterwards
+    async fn tournament_standings(
+        &self,
+        tournament_id: u64,
+    ) -> async_graphql::Result<Vec<TournamentStanding>> {
+        let tournament = self
+            .state
+            .tournaments
+            .get(&tournament_id)
+            .await?
+            .ok_or("Tournament not found")?;
+        Ok(match tournament.finalized_at {
+            Some(_) => tournament.results,
+            None => tournament.standings(),
+        })
+    }
+
+    /// Finalized tournaments with their results, most recently finalized first
+    async fn past_tournaments(
+        &self,
+        #[graphql(default = 10)] limit: usize,
+    ) -> async_graphql::Result<Vec<Tournament>> {
+        let mut tournaments = self
+            .state
+            .tournaments
+            .index_values()
+            .await?
+            .into_iter()
+            .map(|(_, tournament)| tournament)
+            .filter(|tournament| tournament.finalized_at.is_some())
+            .collect::<Vec<_>>();
+        tournaments.sort_by(|a, b| b.finalized_at.cmp(&a.finalized_at));
+        tournaments.truncate(limit);
+        Ok(tournaments)
+    }
+
+    /// The pot of a wagered room that has started
+    async fn room_escrow(&self, room_id: u64) -> async_graphql::Result<Option<Escrow>> {
+        Ok(self.state.room_escrows.get(&room_id).await?)
+    }
+
+    /// Points kept as rake from wagered rooms so far
+    async fn collected_rake(&self) -> u64 {
+        *self.state.collected_rake.get()
+    }
+
+    /// The tick a room's board is waiting on, and who still has to move
+    async fn room_tick(&self, room_id: u64) -> async_graphql::Result<Option<RoomTick>> {
+        let Some(board) = self.state.room_boards.get(&room_id).await? else {
+            return Ok(None);
+        };
+        Ok(Some(RoomTick {
+            tick: board.tick + 1,
+            phase: board.phase,
+            deadline: board.tick_deadline,
+            pending_players: board.pending_players(),
+        }))
+    }
+
+    /// The hash to commit to for a move in a commit-reveal room. Keep the salt secret
+    /// until revealing the move
+    async fn move_commitment(
+        &self,
+        player: AccountOwner,
+        tick: u64,
+        direction: Direction,
+        salt: String,
+    ) -> CryptoHash {
+        MoveCommitment {
+            player,
+            tick,
+            direction,
+            salt,
+        }
+        .hash()
+    }
+
+    /// The lobby a room code leads to, while the room is still open
+    async fn room_by_code(&self, code: String) -> async_graphql::Result<Option<Room>> {
+        let Some(room_id) = self.state.room_codes.get(&code).await? else {
+            return Ok(None);
+        };
+        Ok(self.state.rooms.get(&room_id).await?)
+    }
+
+    /// Lobbies that are waiting for players and still have space
+    async fn open_rooms(&self) -> async_graphql::Result<Vec<Room>> {
+        let mut rooms = Vec::new();
+        for (_, room_id) in self.state.room_codes.index_values().await? {
+            if let Some(room) = self.state.rooms.get(&room_id).await? {
+                if room.status == RoomStatus::Waiting && !room.is_full() {
+                    rooms.push(room);
+                }
+            }
+        }
+        Ok(rooms)
+    }
+
+    /// The room a player is in, if any
+    async fn player_room(&self, player: AccountOwner) -> async_graphql::Result<Option<Room>> {
+        let Some(room_id) = self.state.player_rooms.get(&player).await? else {
+            return Ok(None);
+        };
+        Ok(self.state.rooms.get(&room_id).await?)
+    }
+
+    /// A player's room on another chain, as last reported by that chain, including
+    /// the results once its game is over
+    async fn remote_room(&self, player: AccountOwner) -> async_graphql::Result<Option<RemoteRoom>> {
+        Ok(self.state.remote_rooms.get(&player).await?)
+    }
+
+    /// The rewards a player has redeemed
+    async fn inventory(&self, player: AccountOwner) -> async_graphql::Result<Vec<InventoryItem>> {
+        let Some(inventory) = self.state.inventory.try_load_entry(&player).await? else {
+            return Ok(Vec::new());
+        };
+        Ok(inventory
+            .index_values()
+            .await?
+            .into_iter()
+            .map(|(reward_id, quantity)| InventoryItem {
+                reward_id,
+                quantity,
+            })
+            .collect())
     }
 }
 
@@ -69,10 +729,16 @@ mod tests {
 
     use async_graphql::{Request, Response, Value};
     use futures::FutureExt as _;
-    use linera_sdk::{util::BlockingWait, views::View, Service, ServiceRuntime};
+    use linera_sdk::{
+        linera_base_types::{AccountOwner, CryptoHash, Timestamp},
+        util::BlockingWait,
+        views::View,
+        Service, ServiceRuntime,
+    };
     use serde_json::json;
 
     use super::{JeteeahService, JeteeahState};
+    use jeteeah::state::{LedgerEntry, LedgerEntryKind, LedgerReason};
 
     #[test]
     fn query() {
@@ -83,7 +749,10 @@ mod tests {
             .expect("Failed to read from mock key value store");
         state.value.set(value);
 
-        let service = JeteeahService { state, runtime };
+        let service = JeteeahService {
+            state: Arc::new(state),
+            runtime,
+        };
         let request = Request::new("{ value }");
 
         let response = service
@@ -95,4 +764,364 @@ mod tests {
 
         assert_eq!(response, expected)
     }
+
+    #[test]
+    fn pause_query() {
+        let runtime = Arc::new(ServiceRuntime::<JeteeahService>::new());
+        let mut state = JeteeahState::load(runtime.root_view_storage_context())
+            .blocking_wait()
+            .expect("Failed to read from mock key value store");
+        state.paused.set(true);
+        state
+            .pause_reason
+            .set(Some("Scheduled maintenance".to_string()));
+
+        let service = JeteeahService {
+            state: Arc::new(state),
+            runtime,
+        };
+        let request = Request::new("{ paused pauseReason }");
+
+        let response = service
+            .handle_query(request)
+            .now_or_never()
+            .expect("Query should not await anything");
+
+        let expected = Response::new(
+            Value::from_json(json!({
+                "paused": true,
+                "pauseReason": "Scheduled maintenance",
+            }))
+            .unwrap(),
+        );
+
+        assert_eq!(response, expected)
+    }
+
+    #[test]
+    fn points_history_query() {
+        let runtime = Arc::new(ServiceRuntime::<JeteeahService>::new());
+        let mut state = JeteeahState::load(runtime.root_view_storage_context())
+            .blocking_wait()
+            .expect("Failed to read from mock key value store");
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        let history = state
+            .points_history
+            .load_entry_mut(&player)
+            .blocking_wait()
+            .expect("Failed to load history");
+        for (index, balance) in [10u64, 20, 30].into_iter().enumerate() {
+            history.push(LedgerEntry {
+                kind: LedgerEntryKind::Credit,
+                reason: LedgerReason::admin_grant(),
+                amount: 10,
+                balance,
+                timestamp: Timestamp::from(index as u64),
+            });
+        }
+
+        let service = JeteeahService {
+            state: Arc::new(state),
+            runtime,
+        };
+        let request = Request::new(format!(
+            r#"{{ pointsHistory(player: "{player}", offset: 1, limit: 1) {{ balance }} }}"#
+        ));
+
+        let response = service
+            .handle_query(request)
+            .now_or_never()
+            .expect("Query should not await anything");
+
+        let expected =
+            Response::new(Value::from_json(json!({"pointsHistory": [{"balance": 20}]})).unwrap());
+
+        assert_eq!(response, expected)
+    }
+
+    #[test]
+    fn replay_query() {
+        use jeteeah::state::{Challenge, ChallengeStatus, Direction, GameMode, GameSummary};
+        use linera_sdk::linera_base_types::TimeDelta;
+
+        let runtime = Arc::new(ServiceRuntime::<JeteeahService>::new());
+        let mut state = JeteeahState::load(runtime.root_view_storage_context())
+            .blocking_wait()
+            .expect("Failed to read from mock key value store");
+
+        let player = AccountOwner::from(CryptoHash::from([1u8; 32]));
+        state
+            .archived_games
+            .insert(
+                &0,
+                GameSummary {
+                    id: 0,
+                    player,
+                    score: 10,
+                    length: 4,
+                    steps: 10,
+                    started_at: Timestamp::from(0),
+                    ended_at: Timestamp::from(0),
+                    duration: TimeDelta::ZERO,
+                    mode: GameMode::Classic,
+                    map_id: 0,
+                    width: 20,
+                    height: 20,
+                    seed: 0,
+                    end_cause: None,
+                    revive_count: 0,
+                    config_version: 0,
+                    tournament_id: None,
+                },
+            )
+            .expect("Failed to archive game");
+        let inputs = state
+            .replay_inputs
+            .load_entry_mut(&0)
+            .blocking_wait()
+            .expect("Failed to load replay inputs");
+        for direction in [[Direction::Right; 5], [Direction::Down; 5]].concat() {
+            inputs.push(direction);
+        }
+
+        // A second run is being raced in a challenge the opponent hasn't taken up yet
+        let summary = GameSummary {
+            id: 1,
+            ..state
+                .archived_games
+                .get(&0)
+                .blocking_wait()
+                .expect("Failed to read archived game")
+                .expect("Game should be archived")
+        };
+        state
+            .archived_games
+            .insert(&1, summary)
+            .expect("Failed to archive game");
+        let opponent = AccountOwner::from(CryptoHash::from([2u8; 32]));
+        let challenge = Challenge {
+            id: 0,
+            challenger: player,
+            opponent,
+            game_id: 1,
+            map_id: 0,
+            width: 20,
+            height: 20,
+            seed: 0,
+            stake: 0,
+            challenger_score: 10,
+            opponent_game_id: None,
+            opponent_score: None,
+            winner: None,
+            status: ChallengeStatus::Pending,
+            created_at: Timestamp::from(0),
+            completed_at: None,
+        };
+        state
+            .challenges
+            .insert(&0, challenge)
+            .expect("Failed to add challenge");
+        state
+            .player_challenges
+            .load_entry_mut(&player)
+            .blocking_wait()
+            .expect("Failed to load player challenges")
+            .push(0);
+
+        let service = JeteeahService {
+            state: Arc::new(state),
+            runtime,
+        };
+
+        // The snake starts at (10, 10) and the first food sits at (15, 15)
+        let request =
+            Request::new("{ replay(gameId: 0, frame: 10) { index score snakeBody { x y } } }");
+        let response = service
+            .handle_query(request)
+            .now_or_never()
+            .expect("Query should not await anything");
+        let expected = Response::new(
+            Value::from_json(json!({"replay": [{
+                "index": 10,
+                "score": 10,
+                "snakeBody": [
+                    {"x": 15, "y": 15},
+                    {"x": 15, "y": 14},
+                    {"x": 15, "y": 13},
+                    {"x": 15, "y": 12},
+                ],
+            }]}))
+            .unwrap(),
+        );
+        assert_eq!(response, expected);
+
+        let request = Request::new("{ replay(gameId: 0) { index } }");
+        let response = service
+            .handle_query(request)
+            .now_or_never()
+            .expect("Query should not await anything");
+        let frames = response.data.into_json().unwrap()["replay"]
+            .as_array()
+            .unwrap()
+            .len();
+        assert_eq!(frames, 11);
+
+        // The challenged run's moves stay hidden while the challenge is undecided
+        let request = Request::new("{ replay(gameId: 1) { index } }");
+        let response = service
+            .handle_query(request)
+            .now_or_never()
+            .expect("Query should not await anything");
+        assert!(!response.errors.is_empty());
+    }
+
+    #[test]
+    fn spectate_queries() {
+        use jeteeah::{
+            rules,
+            state::{Direction, GameStatus, Room, RoomStatus},
+        };
+
+        let runtime = Arc::new(ServiceRuntime::<JeteeahService>::new());
+        let mut state = JeteeahState::load(runtime.root_view_storage_context())
+            .blocking_wait()
+            .expect("Failed to read from mock key value store");
+
+        // Two solo games in play and one paused
+        let players = [1u8, 2, 3].map(|byte| AccountOwner::from(CryptoHash::from([byte; 32])));
+        for (id, (player, (score, status))) in players
+            .iter()
+            .zip([
+                (30, GameStatus::Active),
+                (50, GameStatus::Active),
+                (90, GameStatus::Paused),
+            ])
+            .enumerate()
+        {
+            let mut game = rules::new_game(id as u64, 0, 20, 20);
+            game.score = score;
+            game.status = status;
+            state
+                .games
+                .insert(player, game)
+                .expect("Failed to add game");
+            state
+                .active_players
+                .insert(player)
+                .expect("Failed to add active player");
+        }
+        let inputs = state
+            .replay_inputs
+            .load_entry_mut(&0)
+            .blocking_wait()
+            .expect("Failed to load replay inputs");
+        for direction in [Direction::Right, Direction::Right, Direction::Down] {
+            inputs.push(direction);
+        }
+
+        // A room three ticks into its game
+        let board = rules::new_board(7, 0, 20, 20, &players[1..]);
+        state
+            .rooms
+            .insert(
+                &7,
+                Room {
+                    id: 7,
+                    code: "ABCDEF".to_string(),
+                    host: players[1],
+                    capacity: 2,
+                    members: Vec::new(),
+                    status: RoomStatus::Playing,
+                    created_at: Timestamp::from(0),
+                    commit_reveal: false,
+                    stake: 0,
+                    results: Vec::new(),
+                },
+            )
+            .expect("Failed to add room");
+        state
+            .playing_rooms
+            .insert(&7)
+            .expect("Failed to add playing room");
+        state
+            .player_rooms
+            .insert(&players[1], 7)
+            .expect("Failed to add player room");
+        let frames = (0..4)
+            .map(|tick| {
+                let mut frame = board.clone();
+                frame.tick = tick;
+                frame
+            })
+            .collect::<Vec<_>>();
+        state
+            .room_frames
+            .insert(&7, frames)
+            .expect("Failed to add room frames");
+        state
+            .room_boards
+            .insert(&7, board)
+            .expect("Failed to add board");
+
+        let service = JeteeahService {
+            state: Arc::new(state),
+            runtime,
+        };
+        let query = |query: String| {
+            service
+                .handle_query(Request::new(query))
+                .blocking_wait()
+                .data
+                .into_json()
+                .unwrap()
+        };
+
+        assert_eq!(
+            query("{ liveGames { score } liveRooms { id } }".to_string()),
+            json!({
+                "liveGames": [{"score": 50}, {"score": 30}],
+                "liveRooms": [{"id": 7}],
+            })
+        );
+
+        // A player's solo game comes with its latest moves
+        assert_eq!(
+            query(format!(
+                "{{ spectate(target: {{ player: \"{}\" }}, frames: 2) {{ \
+                 game {{ score }} recentFrames {{ index input }} }} }}",
+                players[0]
+            )),
+            json!({"spectate": {
+                "game": {"score": 30},
+                "recentFrames": [
+                    {"index": 2, "input": "RIGHT"},
+                    {"index": 3, "input": "DOWN"},
+                ],
+            }})
+        );
+
+        // A player in a room that's playing is shown the room instead
+        let room_view = json!({"spectate": {
+            "room": {"id": 7},
+            "game": null,
+            "recentBoards": [{"tick": 2}, {"tick": 3}],
+        }});
+        assert_eq!(
+            query(format!(
+                "{{ spectate(target: {{ player: \"{}\" }}, frames: 2) {{ \
+                 room {{ id }} game {{ score }} recentBoards {{ tick }} }} }}",
+                players[1]
+            )),
+            room_view
+        );
+        assert_eq!(
+            query(
+                "{ spectate(target: { roomId: 7 }, frames: 2) { \
+                 room { id } game { score } recentBoards { tick } } }"
+                    .to_string()
+            ),
+            room_view
+        );
+    }
 }
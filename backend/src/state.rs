@@ -1,10 +1,31 @@
 use linera_sdk::{
-    linera_base_types::AccountOwner,
-    views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext},
+    linera_base_types::{
+        AccountOwner, Amount, ApplicationId, BcsHashable, ChainId, CryptoHash, TimeDelta, Timestamp,
+    },
+    views::{
+        linera_views, CollectionView, LogView, MapView, QueueView, RegisterView, RootView, SetView,
+        ViewStorageContext,
+    },
 };
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+/// The skin every player owns from the start
+pub const DEFAULT_SKIN_ID: u32 = 0;
+
+/// The board every player can use, sized by the game parameters
+pub const DEFAULT_MAP_ID: u32 = 0;
+
+/// The fewest and most players a multiplayer room can hold
+pub const MIN_ROOM_CAPACITY: u32 = 2;
+pub const MAX_ROOM_CAPACITY: u32 = 8;
+
+/// How many of a room's latest boards are kept for spectators
+pub const SPECTATOR_FRAMES: usize = 20;
+
+/// Length of a UTC day in microseconds, the unit of chain time
+pub const MICROS_PER_DAY: u64 = 86_400_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, async_graphql::SimpleObject)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
@@ -18,22 +39,965 @@ pub enum Direction {
     Right,
 }
 
+/// Where a game is in its lifecycle
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, async_graphql::Enum, PartialEq, Eq)]
+pub enum GameStatus {
+    Active,
+    Paused,
+    /// The game ended but its score hasn't been paid out yet
+    Over,
+    /// The score has been credited; nothing more can happen to the game
+    Settled,
+}
+
+/// Why a game ended
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, async_graphql::Enum, PartialEq, Eq)]
+pub enum EndCause {
+    WallCollision,
+    SelfCollision,
+    /// The player ended, reset or replaced the game
+    Quit,
+    /// The game ran past the maximum session length
+    TimeLimit,
+    /// The game was abandoned or left paused for too long
+    Expired,
+    /// The snake ran into another snake's body
+    SnakeCollision,
+    /// The snake met another head-on; both are out
+    HeadOn,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
 pub struct GameState {
+    pub id: u64,
     pub snake_body: Vec<Position>,
     pub direction: Direction,
     pub food_position: Position,
     pub score: u64,
-    pub is_active: bool,
-    pub is_paused: bool,
+    pub status: GameStatus,
+    /// Set once the game is over
+    pub end_cause: Option<EndCause>,
+    /// When the snake last crashed, which opens the revive window
+    pub crashed_at: Option<Timestamp>,
+    /// How many times the player revived during this game
+    pub revive_count: u32,
+    pub width: i32,
+    pub height: i32,
+    /// Whether an entry fee was paid to start this game
+    pub ranked: bool,
+    /// The cosmetic the player had equipped when the game started
+    pub skin_id: u32,
+    /// The board the game is played on
+    pub map_id: u32,
+    /// Consecutive moves made without turning
+    pub straight_moves: u64,
+    /// The longest run of moves made without turning so far
+    pub longest_straight: u64,
+    /// Moves made so far
+    pub steps: u64,
+    pub started_at: Timestamp,
+    /// When the last move was made
+    pub last_move_at: Option<Timestamp>,
+    /// When the game ended; cleared again by a revive
+    pub ended_at: Option<Timestamp>,
+    /// When the player last moved, paused, resumed or revived
+    pub last_active_at: Timestamp,
+    /// When the game was paused, while it is
+    pub paused_at: Option<Timestamp>,
+    /// The version of the game settings the game was started under
+    pub config_version: u32,
+    /// Drives food placement, so the game can be replayed from its inputs
+    pub seed: u64,
+    /// The challenge this game is the opponent's attempt at
+    pub challenge_id: Option<u64>,
+    /// The tournament this game was started for
+    pub tournament_id: Option<u64>,
+}
+
+/// The rules a game is played under
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, async_graphql::Enum, PartialEq, Eq)]
+pub enum GameMode {
+    Classic,
+    /// An entry fee was paid to play
+    Ranked,
+}
+
+/// The archived record of a settled game
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct GameSummary {
+    pub id: u64,
+    pub player: AccountOwner,
+    pub score: u64,
+    /// The snake's final length
+    pub length: u64,
+    pub steps: u64,
+    pub started_at: Timestamp,
+    pub ended_at: Timestamp,
+    pub duration: TimeDelta,
+    pub mode: GameMode,
+    pub map_id: u32,
     pub width: i32,
     pub height: i32,
+    pub seed: u64,
+    pub end_cause: Option<EndCause>,
+    pub revive_count: u32,
+    pub config_version: u32,
+    pub tournament_id: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
 pub struct LeaderboardEntry {
     pub player: String,
     pub score: u64,
+    /// Revives used in the run that set the score
+    pub revives: u32,
+}
+
+/// Admin settings for reviving after a collision
+#[derive(
+    Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject, async_graphql::InputObject,
+)]
+#[graphql(input_name = "ReviveConfigInput")]
+pub struct ReviveConfig {
+    /// Revives allowed per game; zero disables reviving
+    pub max_revives: u32,
+    /// How long after a collision a revive is still allowed
+    pub window: TimeDelta,
+    /// Cost of the first revive; the n-th revive in a game costs n times this
+    pub base_cost: u64,
+}
+
+/// The admin-chosen settings of a tournament
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct TournamentDetails {
+    pub name: String,
+    /// Tournament games can be started from this time
+    pub starts_at: Timestamp,
+    /// Tournament games can be started until this time, after which the tournament
+    /// can be finalized
+    pub ends_at: Timestamp,
+    pub mode: GameMode,
+    pub map_id: u32,
+    /// Points paid to register
+    pub entry_fee: u64,
+    pub max_entrants: u32,
+    /// Points paid for each placement, starting with first, as far as the entry fees
+    /// cover them
+    pub prizes: Vec<u64>,
+}
+
+/// A player's registration in a tournament and their best game so far
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct TournamentEntry {
+    pub player: AccountOwner,
+    pub best_score: u64,
+    /// Tournament games the player has finished
+    pub games: u32,
+}
+
+/// A player's place in a tournament's ranking
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct TournamentStanding {
+    pub player: AccountOwner,
+    pub best_score: u64,
+    pub placement: u32,
+    /// The points the placement pays
+    pub prize: u64,
+}
+
+/// A community event where registered players compete for the best score within a
+/// time window
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct Tournament {
+    pub id: u64,
+    pub name: String,
+    pub starts_at: Timestamp,
+    pub ends_at: Timestamp,
+    pub mode: GameMode,
+    pub map_id: u32,
+    pub entry_fee: u64,
+    pub max_entrants: u32,
+    pub prizes: Vec<u64>,
+    /// The entry fees paid so far, which the prizes are paid from
+    pub prize_pool: u64,
+    /// Entrants in the order they registered
+    pub entries: Vec<TournamentEntry>,
+    pub finalized_at: Option<Timestamp>,
+    /// The final ranking, filled in when the tournament is finalized
+    pub results: Vec<TournamentStanding>,
+}
+
+impl Tournament {
+    /// The entrants who finished a tournament game, ranked by best score, with ties
+    /// going to whoever registered first. Prizes are taken from the pool in placement
+    /// order until it runs out
+    pub fn standings(&self) -> Vec<TournamentStanding> {
+        let mut entries = self
+            .entries
+            .iter()
+            .filter(|entry| entry.games > 0)
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| b.best_score.cmp(&a.best_score));
+        let mut pool = self.prize_pool;
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let prize = self.prizes.get(index).copied().unwrap_or(0).min(pool);
+                pool -= prize;
+                TournamentStanding {
+                    player: entry.player,
+                    best_score: entry.best_score,
+                    placement: index as u32 + 1,
+                    prize,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Where a ghost-race challenge stands
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, async_graphql::Enum, PartialEq, Eq)]
+pub enum ChallengeStatus {
+    /// Waiting for the opponent to take it up
+    Pending,
+    /// The opponent is playing the challenge's seed
+    Accepted,
+    Completed,
+    Declined,
+    /// Withdrawn by the challenger before it was taken up
+    Cancelled,
+}
+
+/// A race against a recorded run: the opponent plays the same seed on the same board,
+/// whenever they like, and the better score takes both stakes
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct Challenge {
+    pub id: u64,
+    pub challenger: AccountOwner,
+    pub opponent: AccountOwner,
+    /// The challenger's settled run the opponent races against
+    pub game_id: u64,
+    pub map_id: u32,
+    pub width: i32,
+    pub height: i32,
+    pub seed: u64,
+    /// Points each side puts up; zero for bragging rights only
+    pub stake: u64,
+    pub challenger_score: u64,
+    /// The opponent's game on the challenge's seed, once accepted
+    pub opponent_game_id: Option<u64>,
+    pub opponent_score: Option<u64>,
+    /// The better scorer, or `None` on a draw
+    pub winner: Option<AccountOwner>,
+    pub status: ChallengeStatus,
+    pub created_at: Timestamp,
+    pub completed_at: Option<Timestamp>,
+}
+
+/// Where a multiplayer room is in its lifecycle
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, async_graphql::Enum, PartialEq, Eq)]
+pub enum RoomStatus {
+    /// Players are gathering in the lobby
+    Waiting,
+    Playing,
+    Finished,
+    /// Everyone left before the game started
+    Cancelled,
+}
+
+/// A player in a room's lobby
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct RoomMember {
+    pub player: AccountOwner,
+    pub ready: bool,
+}
+
+/// A multiplayer lobby, joined by its code
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct Room {
+    pub id: u64,
+    pub code: String,
+    pub host: AccountOwner,
+    pub capacity: u32,
+    /// Members in the order they joined
+    pub members: Vec<RoomMember>,
+    pub status: RoomStatus,
+    pub created_at: Timestamp,
+    /// Whether moves are committed as hashes first and revealed once everyone has
+    /// committed, so no player can react to another's move
+    pub commit_reveal: bool,
+    /// Points each player puts into the pot when the room starts, taken from their
+    /// balance on this chain; zero for a friendly game
+    pub stake: u64,
+    /// Final standings, filled in when the room's game is over
+    pub results: Vec<RoomResult>,
+}
+
+/// One player's snake on a room's shared board
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct Snake {
+    pub player: AccountOwner,
+    pub body: Vec<Position>,
+    pub direction: Direction,
+    /// The move submitted, or revealed, for the tick being collected
+    pub next_direction: Option<Direction>,
+    /// The hash of the move committed for the tick being collected
+    pub commitment: Option<CryptoHash>,
+    /// Committed moves the player failed to reveal in time
+    pub missed_reveals: u32,
+    pub score: u64,
+    pub alive: bool,
+    pub end_cause: Option<EndCause>,
+    /// The tick the snake was knocked out on
+    pub eliminated_at: Option<u64>,
+}
+
+/// The shared board of a room's game, where every snake moves on each tick
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct Board {
+    pub room_id: u64,
+    pub width: i32,
+    pub height: i32,
+    pub snakes: Vec<Snake>,
+    pub food_position: Position,
+    /// Ticks played so far
+    pub tick: u64,
+    /// When the current phase of the tick ends even if moves are missing
+    pub tick_deadline: Timestamp,
+    pub seed: u64,
+    pub commit_reveal: bool,
+    pub phase: TickPhase,
+}
+
+/// Which part of a tick is being collected
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, async_graphql::Enum, PartialEq, Eq)]
+pub enum TickPhase {
+    /// Moves, or commitments in commit-reveal rooms
+    Submit,
+    /// Committed moves being revealed
+    Reveal,
+}
+
+impl Board {
+    /// Living players the current phase of the tick is still waiting for
+    pub fn pending_players(&self) -> Vec<AccountOwner> {
+        self.snakes
+            .iter()
+            .filter(|snake| match (self.commit_reveal, self.phase) {
+                (false, _) => snake.alive && snake.next_direction.is_none(),
+                (true, TickPhase::Submit) => snake.alive && snake.commitment.is_none(),
+                (true, TickPhase::Reveal) => {
+                    snake.commitment.is_some() && snake.next_direction.is_none()
+                }
+            })
+            .map(|snake| snake.player)
+            .collect()
+    }
+}
+
+/// A room move as committed to: players submit its hash, then reveal the move and
+/// salt so everyone can check them against it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveCommitment {
+    pub player: AccountOwner,
+    pub tick: u64,
+    pub direction: Direction,
+    /// Random text that keeps the move from being guessed from its hash
+    pub salt: String,
+}
+
+impl BcsHashable<'_> for MoveCommitment {}
+
+impl MoveCommitment {
+    pub fn hash(&self) -> CryptoHash {
+        CryptoHash::new(self)
+    }
+}
+
+/// Admin settings for room games
+#[derive(
+    Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject, async_graphql::InputObject,
+)]
+#[graphql(input_name = "RoomConfigInput")]
+pub struct RoomConfig {
+    /// How long players have to submit their moves, or commitments, for each tick
+    pub tick_duration: TimeDelta,
+    /// How long players in commit-reveal rooms have to reveal their committed moves
+    pub reveal_duration: TimeDelta,
+    /// Points a player loses for each committed move they don't reveal in time
+    pub missed_reveal_penalty: u64,
+    /// The application's cut of each wagered pot, in basis points
+    pub rake_bps: u32,
+}
+
+impl Default for RoomConfig {
+    fn default() -> Self {
+        RoomConfig {
+            tick_duration: TimeDelta::from_secs(2),
+            reveal_duration: TimeDelta::from_secs(2),
+            missed_reveal_penalty: 10,
+            rake_bps: 500,
+        }
+    }
+}
+
+/// A local player's room on another chain, as last reported by that chain
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct RemoteRoom {
+    pub host_chain: ChainId,
+    pub room: Room,
+    pub board: Option<Board>,
+}
+
+/// How a player finished in a room's game
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct RoomResult {
+    pub player: AccountOwner,
+    pub score: u64,
+    /// 1 for the winner; players knocked out on the same tick share a placement
+    pub placement: u32,
+    pub eliminated_at: Option<u64>,
+    /// Points won from a wagered room's pot
+    pub payout: u64,
+}
+
+/// Where a wagered room's pot stands
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, async_graphql::Enum, PartialEq, Eq)]
+pub enum EscrowStatus {
+    /// The game is being played for it
+    Held,
+    PaidOut,
+    /// The room was cancelled and everyone got their stake back
+    Refunded,
+}
+
+/// The stakes of a wagered room, locked from its players' points when it starts
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct Escrow {
+    pub room_id: u64,
+    pub stake: u64,
+    /// Everyone who put in a stake
+    pub players: Vec<AccountOwner>,
+    pub pot: u64,
+    /// The rake in effect when the room started, in basis points
+    pub rake_bps: u32,
+    /// Points the application kept once the pot was paid out
+    pub rake: u64,
+    pub status: EscrowStatus,
+}
+
+impl Room {
+    pub fn member(&self, player: &AccountOwner) -> Option<&RoomMember> {
+        self.members.iter().find(|member| member.player == *player)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.members.len() >= self.capacity as usize
+    }
+
+    /// Whether the host can start: enough players, and all of them ready
+    pub fn can_start(&self) -> bool {
+        self.members.len() >= MIN_ROOM_CAPACITY as usize
+            && self.members.iter().all(|member| member.ready)
+    }
+}
+
+/// Admin settings for when unattended games are expired
+#[derive(
+    Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject, async_graphql::InputObject,
+)]
+#[graphql(input_name = "ExpiryConfigInput")]
+pub struct ExpiryConfig {
+    /// How long a running game may go without any action
+    pub inactivity_timeout: TimeDelta,
+    /// How long a game may stay paused
+    pub max_pause: TimeDelta,
+}
+
+/// A started game waiting for its turn in the sweep for stale games
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct ExpiryEntry {
+    pub player: AccountOwner,
+    pub game_id: u64,
+}
+
+impl Default for ExpiryConfig {
+    fn default() -> Self {
+        ExpiryConfig {
+            inactivity_timeout: TimeDelta::from_secs(60 * 60),
+            max_pause: TimeDelta::from_secs(10 * 60),
+        }
+    }
+}
+
+impl Default for ReviveConfig {
+    fn default() -> Self {
+        ReviveConfig {
+            max_revives: 0,
+            window: TimeDelta::from_secs(10),
+            base_cost: 50,
+        }
+    }
+}
+
+/// A skin handed out on reaching a level
+#[derive(
+    Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject, async_graphql::InputObject,
+)]
+#[graphql(input_name = "LevelSkinInput")]
+pub struct LevelSkin {
+    pub level: u32,
+    pub skin_id: u32,
+}
+
+/// Admin settings for earning XP and turning it into levels
+#[derive(
+    Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject, async_graphql::InputObject,
+)]
+#[graphql(input_name = "ProgressionConfigInput")]
+pub struct ProgressionConfig {
+    /// XP for every step survived
+    pub xp_per_move: u64,
+    pub xp_per_food: u64,
+    /// XP for every game that is settled
+    pub xp_per_game: u64,
+    /// Total XP needed to reach level 2, 3 and so on, in increasing order
+    pub level_xp: Vec<u64>,
+    /// The level needed to play ranked games
+    pub ranked_level: u32,
+    pub level_skins: Vec<LevelSkin>,
+}
+
+impl ProgressionConfig {
+    /// The level a player with this much XP is at, starting from level 1
+    pub fn level_for(&self, xp: u64) -> u32 {
+        1 + self
+            .level_xp
+            .iter()
+            .take_while(|&&needed| xp >= needed)
+            .count() as u32
+    }
+
+    /// The total XP needed to reach the level after `level`, or `None` at the top
+    pub fn next_level_xp(&self, level: u32) -> Option<u64> {
+        self.level_xp.get(level as usize - 1).copied()
+    }
+}
+
+impl Default for ProgressionConfig {
+    fn default() -> Self {
+        ProgressionConfig {
+            xp_per_move: 1,
+            xp_per_food: 10,
+            xp_per_game: 25,
+            level_xp: vec![100, 300, 600, 1_000, 1_500, 2_500, 4_000, 6_000, 9_000],
+            ranked_level: 1,
+            level_skins: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, async_graphql::Enum, PartialEq, Eq)]
+pub enum LedgerEntryKind {
+    Credit,
+    Debit,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, async_graphql::Enum, PartialEq, Eq)]
+pub enum LedgerReasonKind {
+    GameReward,
+    Redemption,
+    AdminGrant,
+    Transfer,
+    TransferRefund,
+    DailyReward,
+    Revive,
+    Achievement,
+    /// Points put into a wagered room's pot or a challenge
+    Stake,
+    /// A stake returned from a room that never got going, or a challenge that was
+    /// called off or drawn
+    StakeRefund,
+    /// A share of a wagered room's pot, or a won challenge's stakes
+    Winnings,
+    TournamentEntry,
+    TournamentPrize,
+    /// Rake collected from wagered rooms and tournaments, paid to the admin
+    RakeWithdrawal,
+}
+
+/// Why a player's points balance changed
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct LedgerReason {
+    pub kind: LedgerReasonKind,
+    /// The game that paid out a reward
+    pub game_id: Option<u64>,
+    /// The other side of a transfer
+    pub counterparty: Option<AccountOwner>,
+    /// The catalog reward that was redeemed
+    pub reward_id: Option<u64>,
+    /// The achievement that paid out
+    pub achievement_id: Option<u32>,
+    /// The wagered room a stake went into or came out of
+    pub room_id: Option<u64>,
+    /// The challenge a stake went into or came out of
+    pub challenge_id: Option<u64>,
+    /// The tournament entered or placed in
+    pub tournament_id: Option<u64>,
+}
+
+impl LedgerReason {
+    fn of(kind: LedgerReasonKind) -> Self {
+        LedgerReason {
+            kind,
+            game_id: None,
+            counterparty: None,
+            reward_id: None,
+            achievement_id: None,
+            room_id: None,
+            challenge_id: None,
+            tournament_id: None,
+        }
+    }
+
+    pub fn game_reward(game_id: u64) -> Self {
+        LedgerReason {
+            game_id: Some(game_id),
+            ..Self::of(LedgerReasonKind::GameReward)
+        }
+    }
+
+    pub fn redemption() -> Self {
+        Self::of(LedgerReasonKind::Redemption)
+    }
+
+    pub fn reward_redemption(reward_id: u64) -> Self {
+        LedgerReason {
+            reward_id: Some(reward_id),
+            ..Self::of(LedgerReasonKind::Redemption)
+        }
+    }
+
+    pub fn admin_grant() -> Self {
+        Self::of(LedgerReasonKind::AdminGrant)
+    }
+
+    pub fn transfer(counterparty: AccountOwner) -> Self {
+        LedgerReason {
+            counterparty: Some(counterparty),
+            ..Self::of(LedgerReasonKind::Transfer)
+        }
+    }
+
+    pub fn daily_reward() -> Self {
+        Self::of(LedgerReasonKind::DailyReward)
+    }
+
+    pub fn revive(game_id: u64) -> Self {
+        LedgerReason {
+            game_id: Some(game_id),
+            ..Self::of(LedgerReasonKind::Revive)
+        }
+    }
+
+    pub fn achievement(achievement_id: u32) -> Self {
+        LedgerReason {
+            achievement_id: Some(achievement_id),
+            ..Self::of(LedgerReasonKind::Achievement)
+        }
+    }
+
+    pub fn transfer_refund(counterparty: AccountOwner) -> Self {
+        LedgerReason {
+            counterparty: Some(counterparty),
+            ..Self::of(LedgerReasonKind::TransferRefund)
+        }
+    }
+
+    pub fn stake(room_id: u64) -> Self {
+        LedgerReason {
+            room_id: Some(room_id),
+            ..Self::of(LedgerReasonKind::Stake)
+        }
+    }
+
+    pub fn stake_refund(room_id: u64) -> Self {
+        LedgerReason {
+            room_id: Some(room_id),
+            ..Self::of(LedgerReasonKind::StakeRefund)
+        }
+    }
+
+    pub fn winnings(room_id: u64) -> Self {
+        LedgerReason {
+            room_id: Some(room_id),
+            ..Self::of(LedgerReasonKind::Winnings)
+        }
+    }
+
+    pub fn challenge_stake(challenge_id: u64) -> Self {
+        LedgerReason {
+            challenge_id: Some(challenge_id),
+            ..Self::of(LedgerReasonKind::Stake)
+        }
+    }
+
+    pub fn challenge_refund(challenge_id: u64) -> Self {
+        LedgerReason {
+            challenge_id: Some(challenge_id),
+            ..Self::of(LedgerReasonKind::StakeRefund)
+        }
+    }
+
+    pub fn challenge_winnings(challenge_id: u64) -> Self {
+        LedgerReason {
+            challenge_id: Some(challenge_id),
+            ..Self::of(LedgerReasonKind::Winnings)
+        }
+    }
+
+    pub fn tournament_entry(tournament_id: u64) -> Self {
+        LedgerReason {
+            tournament_id: Some(tournament_id),
+            ..Self::of(LedgerReasonKind::TournamentEntry)
+        }
+    }
+
+    pub fn tournament_prize(tournament_id: u64) -> Self {
+        LedgerReason {
+            tournament_id: Some(tournament_id),
+            ..Self::of(LedgerReasonKind::TournamentPrize)
+        }
+    }
+
+    pub fn rake_withdrawal() -> Self {
+        Self::of(LedgerReasonKind::RakeWithdrawal)
+    }
+}
+
+/// A single credit or debit in a player's points ledger
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct LedgerEntry {
+    pub kind: LedgerEntryKind,
+    pub reason: LedgerReason,
+    pub amount: u64,
+    /// The balance right after this entry was applied
+    pub balance: u64,
+    pub timestamp: Timestamp,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, async_graphql::Enum, PartialEq, Eq)]
+pub enum RewardKind {
+    Skin,
+    Token,
+    Cosmetic,
+    Consumable,
+    Merchandise,
+}
+
+/// What a `Skin` or `Token` reward hands out when redeemed, fixed when the reward
+/// is created
+#[derive(Debug, Clone, Default, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct RewardPayout {
+    /// The skin granted by a `Skin` reward
+    pub skin_id: Option<u32>,
+    /// The tokens paid out by a `Token` reward
+    pub token_amount: Option<Amount>,
+}
+
+/// An entry in the admin-managed reward catalog
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct Reward {
+    pub id: u64,
+    pub name: String,
+    pub price: u64,
+    /// Remaining units, or `None` for unlimited stock
+    pub stock: Option<u64>,
+    pub kind: RewardKind,
+    /// The skin granted by a `Skin` reward
+    pub skin_id: Option<u32>,
+    /// The tokens paid out by a `Token` reward
+    pub token_amount: Option<Amount>,
+    /// Retired rewards stay in the catalog for history but can't be redeemed
+    pub retired: bool,
+}
+
+/// How many units of a reward a player holds
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct InventoryItem {
+    pub reward_id: u64,
+    pub quantity: u64,
+}
+
+/// An admin-defined board players can choose to play on
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct GameMap {
+    pub id: u32,
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    /// The level a player needs to start a game here
+    pub min_level: u32,
+}
+
+impl GameMap {
+    /// The default map, sized by the current game parameters
+    pub fn classic(width: i32, height: i32) -> Self {
+        GameMap {
+            id: DEFAULT_MAP_ID,
+            name: "Classic".to_string(),
+            width,
+            height,
+            min_level: 1,
+        }
+    }
+}
+
+/// What a player has to do to unlock an achievement
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, async_graphql::Enum, PartialEq, Eq)]
+pub enum AchievementKind {
+    /// Reach a score in a single game
+    Score,
+    /// Grow the snake to a length
+    SnakeLength,
+    /// Finish a number of games
+    GamesPlayed,
+    /// Make a number of moves in a row without turning
+    NoTurnStreak,
+    /// Finish a game on a given map
+    FirstGameOnMap,
+}
+
+/// The admin-editable fields of an achievement
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::InputObject)]
+pub struct AchievementDetails {
+    pub name: String,
+    pub description: String,
+    pub kind: AchievementKind,
+    /// The score, length, game count or move count to reach; unused for maps
+    pub threshold: u64,
+    /// The map a `FirstGameOnMap` achievement is for
+    pub map_id: Option<u32>,
+    /// Points paid when the achievement unlocks
+    pub reward_points: u64,
+    /// A skin granted when the achievement unlocks
+    pub reward_skin: Option<u32>,
+}
+
+/// An achievement players can unlock
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct Achievement {
+    pub id: u32,
+    pub name: String,
+    pub description: String,
+    pub kind: AchievementKind,
+    /// The score, length, game count or move count to reach; unused for maps
+    pub threshold: u64,
+    /// The map a `FirstGameOnMap` achievement is for
+    pub map_id: Option<u32>,
+    /// Points paid when the achievement unlocks
+    pub reward_points: u64,
+    /// A skin granted when the achievement unlocks
+    pub reward_skin: Option<u32>,
+}
+
+impl Achievement {
+    /// Whether a game, and the number of games the player has finished, meet the goal
+    pub fn is_met(&self, game: &GameState, games_played: u64) -> bool {
+        match self.kind {
+            AchievementKind::Score => game.score >= self.threshold,
+            AchievementKind::SnakeLength => game.snake_body.len() as u64 >= self.threshold,
+            AchievementKind::GamesPlayed => games_played >= self.threshold,
+            AchievementKind::NoTurnStreak => game.longest_straight >= self.threshold,
+            AchievementKind::FirstGameOnMap => {
+                game.status == GameStatus::Settled && self.map_id == Some(game.map_id)
+            }
+        }
+    }
+}
+
+impl GameState {
+    /// Whether the game has ended, settled or not
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status, GameStatus::Over | GameStatus::Settled)
+    }
+
+    /// Whether the game has been left alone long enough to be expired: running or
+    /// paused past the limits, or crashed with the revive window closed
+    pub fn is_stale(
+        &self,
+        now: Timestamp,
+        expiry: &ExpiryConfig,
+        revive_window: TimeDelta,
+    ) -> bool {
+        let deadline = match self.status {
+            GameStatus::Active => self
+                .last_active_at
+                .saturating_add(expiry.inactivity_timeout),
+            GameStatus::Paused => self
+                .paused_at
+                .unwrap_or(self.last_active_at)
+                .saturating_add(expiry.max_pause),
+            GameStatus::Over => self
+                .crashed_at
+                .unwrap_or(self.last_active_at)
+                .saturating_add(revive_window),
+            GameStatus::Settled => return false,
+        };
+        now > deadline
+    }
+
+    pub fn mode(&self) -> GameMode {
+        if self.ranked {
+            GameMode::Ranked
+        } else {
+            GameMode::Classic
+        }
+    }
+}
+
+/// A player's daily login streak
+#[derive(Debug, Clone, Default, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct DailyStreak {
+    /// Consecutive days claimed, ending with `last_claim_day`
+    pub streak: u32,
+    /// The UTC day number of the latest claim
+    pub last_claim_day: Option<u64>,
+}
+
+impl DailyStreak {
+    /// The UTC day number a timestamp falls on
+    pub fn day_of(timestamp: Timestamp) -> u64 {
+        timestamp.micros() / MICROS_PER_DAY
+    }
+
+    /// The streak as of `today`, which is zero once a day has been missed
+    pub fn current_streak(&self, today: u64) -> u32 {
+        match self.last_claim_day {
+            Some(day) if day + 1 >= today => self.streak,
+            _ => 0,
+        }
+    }
+
+    /// The earliest time the next claim is allowed
+    pub fn next_claim_time(&self) -> Timestamp {
+        match self.last_claim_day {
+            Some(day) => Timestamp::from((day + 1) * MICROS_PER_DAY),
+            None => Timestamp::from(0),
+        }
+    }
+}
+
+/// The fungible-token application used for entry fees and prizes
+#[derive(
+    Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject, async_graphql::InputObject,
+)]
+#[graphql(input_name = "TokenConfigInput")]
+pub struct TokenConfig {
+    pub application_id: ApplicationId,
+    /// Charged to the player when starting a ranked game
+    pub ranked_entry_fee: Amount,
 }
 
 #[derive(RootView, async_graphql::SimpleObject)]
@@ -42,8 +1006,94 @@ pub struct JeteeahState {
     pub value: RegisterView<u64>,
     pub games: MapView<AccountOwner, GameState>,
     pub high_scores: MapView<AccountOwner, u64>,
+    /// Revives used in the run behind each high score
+    pub high_score_revives: MapView<AccountOwner, u32>,
     pub points: MapView<AccountOwner, u64>,
+    /// Append-only record of every change to each player's points
+    pub points_history: CollectionView<AccountOwner, LogView<LedgerEntry>>,
+    pub next_game_id: RegisterView<u64>,
+    pub rewards: MapView<u64, Reward>,
+    pub next_reward_id: RegisterView<u64>,
+    /// Units of each redeemed reward held by each player
+    pub inventory: CollectionView<AccountOwner, MapView<u64, u64>>,
+    /// Skins each player has bought or been granted, besides the default one
+    pub owned_skins: CollectionView<AccountOwner, SetView<u32>>,
+    pub equipped_skins: MapView<AccountOwner, u32>,
+    /// Boards besides the default one
+    pub maps: MapView<u32, GameMap>,
+    pub next_map_id: RegisterView<u32>,
+    pub achievements: MapView<u32, Achievement>,
+    pub next_achievement_id: RegisterView<u32>,
+    /// When each player unlocked each of their achievements
+    pub unlocked_achievements: CollectionView<AccountOwner, MapView<u32, Timestamp>>,
+    /// Settled games per player
+    pub games_played: MapView<AccountOwner, u64>,
+    pub rooms: MapView<u64, Room>,
+    pub next_room_id: RegisterView<u64>,
+    /// The room each code belongs to, for rooms that are still open
+    pub room_codes: MapView<String, u64>,
+    /// The room each player is currently in
+    pub player_rooms: MapView<AccountOwner, u64>,
+    /// The shared board of each room that has started
+    pub room_boards: MapView<u64, Board>,
+    /// Rooms whose game is being played
+    pub playing_rooms: SetView<u64>,
+    /// Each started room's boards after its latest ticks, oldest first, for spectators
+    pub room_frames: MapView<u64, Vec<Board>>,
+    pub room_config: RegisterView<RoomConfig>,
+    /// The pot of each wagered room that has started
+    pub room_escrows: MapView<u64, Escrow>,
+    /// Points kept as rake from wagered rooms and tournaments, not yet withdrawn by the
+    /// admin
+    pub collected_rake: RegisterView<u64>,
+    pub challenges: MapView<u64, Challenge>,
+    pub next_challenge_id: RegisterView<u64>,
+    /// The challenges each player issued or received, oldest first
+    pub player_challenges: CollectionView<AccountOwner, LogView<u64>>,
+    pub tournaments: MapView<u64, Tournament>,
+    pub next_tournament_id: RegisterView<u64>,
+    /// The chain each player who joined a room here from elsewhere plays from
+    pub player_chains: MapView<AccountOwner, ChainId>,
+    /// The chain hosting the room each player here joined remotely, while in it
+    pub remote_room_hosts: MapView<AccountOwner, ChainId>,
+    /// The latest reported state of each player's room on another chain
+    pub remote_rooms: MapView<AccountOwner, RemoteRoom>,
+    /// Players whose latest game hasn't been settled yet
+    pub active_players: SetView<AccountOwner>,
+    /// Started games in the order the sweep for stale games visits them; games still
+    /// in play go back to the end
+    pub expiry_queue: QueueView<ExpiryEntry>,
+    pub expiry_config: RegisterView<ExpiryConfig>,
+    /// Time spent in settled games per player
+    pub play_time: MapView<AccountOwner, TimeDelta>,
+    /// How long a game may run before it's ended, if limited
+    pub max_session: RegisterView<Option<TimeDelta>>,
+    /// Summaries of every settled game, by game id
+    pub archived_games: MapView<u64, GameSummary>,
+    /// The ids of each player's settled games, oldest first
+    pub game_history: CollectionView<AccountOwner, LogView<u64>>,
+    /// Every move that changed each game, by game id, for replays
+    pub replay_inputs: CollectionView<u64, LogView<Direction>>,
+    /// Bumped whenever an admin changes the rules games are played under
+    pub config_version: RegisterView<u32>,
+    pub daily_streaks: MapView<AccountOwner, DailyStreak>,
+    /// Points paid for each day of a streak; the last entry repeats for longer streaks
+    pub daily_reward_schedule: RegisterView<Vec<u64>>,
+    pub revive_config: RegisterView<ReviveConfig>,
+    /// Experience earned by each player; unlike points it's never spent
+    pub xp: MapView<AccountOwner, u64>,
+    pub progression: RegisterView<ProgressionConfig>,
+    /// The optional token integration, unset when points are the only currency
+    pub token: RegisterView<Option<TokenConfig>>,
     pub game_width: RegisterView<i32>,
     pub game_height: RegisterView<i32>,
     pub food_spawn_rate: RegisterView<u64>,
+    /// The most inputs a run played off-chain may submit
+    pub run_step_limit: RegisterView<u64>,
+    /// The owner allowed to run administrative operations
+    pub admin: RegisterView<Option<AccountOwner>>,
+    /// Whether gameplay and points operations are frozen by an admin
+    pub paused: RegisterView<bool>,
+    /// Why the application was paused, shown in the maintenance banner
+    pub pause_reason: RegisterView<Option<String>>,
 }
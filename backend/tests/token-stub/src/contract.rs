@@ -0,0 +1,105 @@
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use linera_sdk::{
+    abis::fungible::{
+        FungibleOperation, FungibleResponse, FungibleTokenAbi, InitialState, Parameters,
+    },
+    linera_base_types::{AccountOwner, Amount, WithContractAbi},
+    views::{RootView, View},
+    Contract, ContractRuntime,
+};
+
+use self::state::TokenStubState;
+
+pub struct TokenStubContract {
+    state: TokenStubState,
+    runtime: ContractRuntime<Self>,
+}
+
+linera_sdk::contract!(TokenStubContract);
+
+impl WithContractAbi for TokenStubContract {
+    type Abi = FungibleTokenAbi;
+}
+
+impl Contract for TokenStubContract {
+    type Message = ();
+    type Parameters = Parameters;
+    type InstantiationArgument = InitialState;
+    type EventValue = ();
+
+    async fn load(runtime: ContractRuntime<Self>) -> Self {
+        let state = TokenStubState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        TokenStubContract { state, runtime }
+    }
+
+    async fn instantiate(&mut self, argument: Self::InstantiationArgument) {
+        for (owner, amount) in argument.accounts {
+            self.state
+                .accounts
+                .insert(&owner, amount)
+                .expect("Failed to insert account");
+        }
+    }
+
+    async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
+        match operation {
+            FungibleOperation::Balance { owner } => {
+                FungibleResponse::Balance(self.balance(&owner).await)
+            }
+            FungibleOperation::TickerSymbol => {
+                FungibleResponse::TickerSymbol(self.runtime.application_parameters().ticker_symbol)
+            }
+            FungibleOperation::Transfer {
+                owner,
+                amount,
+                target_account,
+            } => {
+                self.runtime
+                    .check_account_permission(owner)
+                    .expect("Permission for transfer denied");
+                assert_eq!(
+                    target_account.chain_id,
+                    self.runtime.chain_id(),
+                    "The stand-in token only supports transfers on its own chain"
+                );
+
+                let balance = self.balance(&owner).await;
+                let remaining = balance.try_sub(amount).expect("Insufficient balance");
+                self.state
+                    .accounts
+                    .insert(&owner, remaining)
+                    .expect("Failed to debit account");
+
+                let target_balance = self.balance(&target_account.owner).await;
+                self.state
+                    .accounts
+                    .insert(&target_account.owner, target_balance.saturating_add(amount))
+                    .expect("Failed to credit account");
+                FungibleResponse::Ok
+            }
+            _ => panic!("Operation not supported by the stand-in token"),
+        }
+    }
+
+    async fn execute_message(&mut self, _message: Self::Message) {}
+
+    async fn store(mut self) {
+        self.state.save().await.expect("Failed to save state");
+    }
+}
+
+impl TokenStubContract {
+    async fn balance(&self, owner: &AccountOwner) -> Amount {
+        self.state
+            .accounts
+            .get(owner)
+            .await
+            .expect("Failed to read account")
+            .unwrap_or_default()
+    }
+}
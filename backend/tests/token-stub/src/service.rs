@@ -0,0 +1,62 @@
+#![cfg_attr(target_arch = "wasm32", no_main)]
+
+mod state;
+
+use std::sync::Arc;
+
+use async_graphql::{EmptyMutation, EmptySubscription, Object, Request, Response, Schema};
+use linera_sdk::{
+    abis::fungible::FungibleTokenAbi,
+    linera_base_types::{AccountOwner, Amount, WithServiceAbi},
+    views::View,
+    Service, ServiceRuntime,
+};
+
+use self::state::TokenStubState;
+
+pub struct TokenStubService {
+    state: Arc<TokenStubState>,
+}
+
+linera_sdk::service!(TokenStubService);
+
+impl WithServiceAbi for TokenStubService {
+    type Abi = FungibleTokenAbi;
+}
+
+impl Service for TokenStubService {
+    type Parameters = linera_sdk::abis::fungible::Parameters;
+
+    async fn new(runtime: ServiceRuntime<Self>) -> Self {
+        let state = TokenStubState::load(runtime.root_view_storage_context())
+            .await
+            .expect("Failed to load state");
+        TokenStubService {
+            state: Arc::new(state),
+        }
+    }
+
+    async fn handle_query(&self, query: Request) -> Response {
+        Schema::build(
+            QueryRoot {
+                state: self.state.clone(),
+            },
+            EmptyMutation,
+            EmptySubscription,
+        )
+        .finish()
+        .execute(query)
+        .await
+    }
+}
+
+struct QueryRoot {
+    state: Arc<TokenStubState>,
+}
+
+#[Object]
+impl QueryRoot {
+    async fn balance(&self, owner: AccountOwner) -> async_graphql::Result<Amount> {
+        Ok(self.state.accounts.get(&owner).await?.unwrap_or_default())
+    }
+}
@@ -0,0 +1,10 @@
+use linera_sdk::{
+    linera_base_types::{AccountOwner, Amount},
+    views::{linera_views, MapView, RootView, ViewStorageContext},
+};
+
+#[derive(RootView)]
+#[view(context = ViewStorageContext)]
+pub struct TokenStubState {
+    pub accounts: MapView<AccountOwner, Amount>,
+}
@@ -0,0 +1,101 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration testing for the optional fungible-token integration.
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use jeteeah::{
+    state::{RewardKind, RewardPayout, TokenConfig},
+    Operation,
+};
+use linera_sdk::{
+    abis::fungible::{FungibleTokenAbi, InitialState, InitialStateBuilder, Parameters},
+    linera_base_types::{AccountOwner, Amount},
+    test::{QueryOutcome, TestValidator},
+};
+
+/// Tests charging the ranked entry fee and paying out a token reward through a token
+/// application
+///
+/// Publishes the stand-in token application from `tests/token-stub`, funds the chain owner
+/// with 10 tokens, then starts a ranked game with a 3 token entry fee, redeems a reward
+/// paying 2 tokens, and checks that only the difference stayed in the jeteeah
+/// application's account
+#[tokio::test(flavor = "multi_thread")]
+async fn ranked_entry_fee_and_token_reward_test() {
+    let (validator, module_id) =
+        TestValidator::with_current_module::<jeteeah::JeteeahAbi, (), u64>().await;
+    let mut chain = validator.new_chain().await;
+    let player = AccountOwner::from(chain.public_key());
+
+    let token_module = chain
+        .publish_bytecode_files_in::<FungibleTokenAbi, Parameters, InitialState>("tests/token-stub")
+        .await;
+    let token_id = chain
+        .create_application(
+            token_module,
+            Parameters::new("JET"),
+            InitialStateBuilder::default()
+                .with_account(player, Amount::from_tokens(10))
+                .build(),
+            vec![],
+        )
+        .await;
+
+    let application_id = chain
+        .create_application(module_id, (), 0u64, vec![token_id.forget_abi()])
+        .await;
+
+    chain
+        .add_block(|block| {
+            block.with_operation(
+                application_id,
+                Operation::ConfigureToken {
+                    config: Some(TokenConfig {
+                        application_id: token_id.forget_abi(),
+                        ranked_entry_fee: Amount::from_tokens(3),
+                    }),
+                },
+            );
+            block.with_operation(application_id, Operation::StartRankedGame);
+            block.with_operation(
+                application_id,
+                Operation::AddPoints {
+                    target: player,
+                    amount: 50,
+                },
+            );
+            block.with_operation(
+                application_id,
+                Operation::CreateReward {
+                    name: "Token bundle".to_string(),
+                    price: 50,
+                    stock: None,
+                    kind: RewardKind::Token,
+                    payout: Some(RewardPayout {
+                        skin_id: None,
+                        token_amount: Some(Amount::from_tokens(2)),
+                    }),
+                },
+            );
+            block.with_operation(application_id, Operation::RedeemReward { reward_id: 0 });
+        })
+        .await;
+
+    for (owner, expected) in [
+        (player, Amount::from_tokens(9)),
+        (AccountOwner::from(application_id), Amount::from_tokens(1)),
+    ] {
+        let QueryOutcome { response, .. } = chain
+            .graphql_query(token_id, format!("query {{ balance(owner: \"{owner}\") }}"))
+            .await;
+        let balance = response["balance"]
+            .as_str()
+            .expect("Failed to get the balance")
+            .parse::<Amount>()
+            .expect("Failed to parse the balance");
+
+        assert_eq!(balance, expected);
+    }
+}
@@ -5,6 +5,7 @@
 
 import { lineraClient, lineraQueryClient, isMockMode } from "./linera-client";
 import type {
+  AddPointsParams,
   Direction,
   GameState,
   GameStateResponse,
@@ -446,11 +447,16 @@ export class SnakeContract {
 
   /**
    * Add points to a player's account (admin function)
+   * @param target - Wallet address of the player receiving the points
+   * @param amount - Points to grant
    */
-  static async addPoints(amount: number): Promise<OperationResult> {
+  static async addPoints(
+    target: AddPointsParams["target"],
+    amount: AddPointsParams["amount"]
+  ): Promise<OperationResult> {
     try {
       if (isMockMode()) {
-        console.log(`🧪 [MOCK] Adding ${amount} points`);
+        console.log(`🧪 [MOCK] Adding ${amount} points to ${target}`);
         return { success: true, transactionId: "mock-tx-add-points" };
       }
 
@@ -458,6 +464,7 @@ export class SnakeContract {
         mutation {
           operation(operation: {
             AddPoints: {
+              target: "${target}"
               amount: ${amount}
             }
           })
@@ -465,7 +472,7 @@ export class SnakeContract {
       `;
 
       const result = await lineraClient.request(mutation);
-      console.log(`➕ Added ${amount} points`);
+      console.log(`➕ Added ${amount} points to ${target}`);
       return {
         success: true,
         data: result,
@@ -97,6 +97,12 @@ export type OperationType =
   | 'UpdateFoodSpawnRate'
   | 'ResetLeaderboard';
 
+// AddPoints operation payload - matches smart contract Operation::AddPoints
+export interface AddPointsParams {
+  target: string;
+  amount: number;
+}
+
 // GraphQL query response types
 export interface GameStateResponse {
   games: GameState | null;